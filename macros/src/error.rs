@@ -1,5 +1,5 @@
 use proc_macro::TokenStream;
-use quote::quote;
+use quote::{format_ident, quote};
 use syn::*;
 
 pub fn derive_error_impl(input: TokenStream) -> TokenStream {
@@ -18,8 +18,16 @@ pub fn derive_error_impl(input: TokenStream) -> TokenStream {
              message,
              field_count,
          }| {
-            let field_placeholders = get_field_placeholders(field_count);
-            quote! { #input_ident::#ident#field_placeholders => #message }
+            // 메시지에 `{0}`과 같은 자리표시자가 있다면 필드 값으로 채움
+            if message.value().contains('{') && field_count > 0 {
+                let fields = (0..field_count)
+                    .map(|i| format_ident!("field_{}", i))
+                    .collect::<Vec<_>>();
+                quote! { #input_ident::#ident(#(#fields),*) => format!(#message, #(#fields),*) }
+            } else {
+                let field_placeholders = get_field_placeholders(field_count);
+                quote! { #input_ident::#ident#field_placeholders => #message.to_owned() }
+            }
         },
     );
     let code_match_arms = iter.clone().map(std::result::Result::unwrap).map(
@@ -39,7 +47,6 @@ pub fn derive_error_impl(input: TokenStream) -> TokenStream {
                 match self {
                     #(#message_match_arms,)*
                 }
-                .to_owned()
             }
 
             fn code(&self) -> String {
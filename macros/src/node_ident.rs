@@ -32,8 +32,12 @@ pub fn node_ident_impl(input: TokenStream) -> TokenStream {
                 }
             }
 
-            pub async fn resolve(self, uuid: &::uuid::Uuid, pool: &::sqlx::PgPool)
-                -> ::std::option::Option<::std::option::Option<#input_ident>> {
+            pub async fn resolve(
+                self,
+                ctx: &::async_graphql::Context<'_>,
+                uuid: &::uuid::Uuid,
+                pool: &::sqlx::PgPool,
+            ) -> ::std::option::Option<::std::option::Option<#input_ident>> {
                 match self {
                     #(#res_match_arms,)*
                     _ => ::std::option::Option::None
@@ -92,7 +96,7 @@ fn map_variants(variant: &Variant) -> MappedVariant {
         to_match_arm: quote! {NodeIdent::#ident => #str_lit},
         res_match_arm: resolver_fn_ident.map(|resolver_fn_ident| {
             quote! {
-                NodeIdent::#ident => ::std::option::Option::Some(#resolver_fn_ident(uuid, pool).await)
+                NodeIdent::#ident => ::std::option::Option::Some(#resolver_fn_ident(ctx, uuid, pool).await)
             }
         }),
     }
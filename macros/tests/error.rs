@@ -0,0 +1,50 @@
+use webgame_collection_api_macros::Error;
+
+// 매크로가 생성하는 구현은 `crate::error::Error`를 가리키므로 테스트 크레이트에도 같은 트레이트를 둠
+mod error {
+    pub trait Error {
+        fn message(&self) -> String;
+        fn code(&self) -> String;
+    }
+}
+
+use error::Error as _;
+
+#[derive(Error)]
+enum TestError {
+    #[error(message = "Plain message")]
+    Plain,
+    #[error(message = "Message ignoring its field")]
+    WithField(i64),
+    #[error(message = "Try again in {0} seconds")]
+    Placeholder(i64),
+    #[error(message = "Between {0} and {1}")]
+    Placeholders(i64, &'static str),
+}
+
+#[test]
+fn returns_plain_message() {
+    assert_eq!(TestError::Plain.message(), "Plain message");
+    assert_eq!(
+        TestError::WithField(3).message(),
+        "Message ignoring its field"
+    );
+}
+
+#[test]
+fn fills_placeholders_with_fields() {
+    assert_eq!(
+        TestError::Placeholder(30).message(),
+        "Try again in 30 seconds"
+    );
+    assert_eq!(
+        TestError::Placeholders(1, "two").message(),
+        "Between 1 and two"
+    );
+}
+
+#[test]
+fn prefixes_code_with_enum_name() {
+    assert_eq!(TestError::Plain.code(), "TestError::Plain");
+    assert_eq!(TestError::Placeholder(30).code(), "TestError::Placeholder");
+}
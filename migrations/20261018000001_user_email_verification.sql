@@ -0,0 +1,2 @@
+ALTER TABLE public.user
+    ADD COLUMN email_verified_at TIMESTAMPTZ;
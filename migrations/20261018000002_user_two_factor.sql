@@ -0,0 +1,8 @@
+CREATE TABLE public.user_two_factor (
+    user_id UUID PRIMARY KEY REFERENCES public.user (id) ON DELETE CASCADE,
+    secret TEXT NOT NULL,
+    recovery_codes TEXT[] NOT NULL DEFAULT '{}',
+    last_used_step BIGINT,
+    enabled_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
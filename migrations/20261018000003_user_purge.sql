@@ -0,0 +1,2 @@
+ALTER TABLE public.user
+    ADD COLUMN purged_at TIMESTAMPTZ;
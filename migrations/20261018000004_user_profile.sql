@@ -0,0 +1,4 @@
+ALTER TABLE public.user
+    ADD COLUMN avatar_url TEXT,
+    ADD COLUMN bio TEXT,
+    ADD COLUMN nickname_changed_at TIMESTAMPTZ;
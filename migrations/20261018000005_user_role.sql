@@ -0,0 +1,8 @@
+CREATE TYPE user_role_type AS ENUM ('admin', 'moderator', 'developer');
+
+CREATE TABLE public.user_role (
+    user_id UUID NOT NULL REFERENCES public.user (id) ON DELETE CASCADE,
+    role user_role_type NOT NULL,
+    granted_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (user_id, role)
+);
@@ -0,0 +1,3 @@
+ALTER TABLE public.user
+    ADD COLUMN is_guest BOOLEAN NOT NULL DEFAULT FALSE,
+    ADD COLUMN last_active_at TIMESTAMPTZ;
@@ -0,0 +1,14 @@
+CREATE TABLE public.api_key (
+    id UUID PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES public.user (id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    prefix TEXT NOT NULL,
+    key_hash TEXT NOT NULL UNIQUE,
+    scopes TEXT[] NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    expires_at TIMESTAMPTZ,
+    last_used_at TIMESTAMPTZ,
+    revoked_at TIMESTAMPTZ
+);
+
+CREATE INDEX api_key_user_id_idx ON public.api_key (user_id);
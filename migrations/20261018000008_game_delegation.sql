@@ -0,0 +1,18 @@
+CREATE TABLE public.game_consent (
+    user_id UUID NOT NULL REFERENCES public.user (id) ON DELETE CASCADE,
+    game_id UUID NOT NULL REFERENCES public.game (id) ON DELETE CASCADE,
+    scopes TEXT[] NOT NULL,
+    granted_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    revoked_at TIMESTAMPTZ,
+    PRIMARY KEY (user_id, game_id)
+);
+
+CREATE TABLE public.game_score (
+    id UUID PRIMARY KEY,
+    game_id UUID NOT NULL REFERENCES public.game (id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES public.user (id) ON DELETE CASCADE,
+    score BIGINT NOT NULL,
+    submitted_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX game_score_game_id_score_idx ON public.game_score (game_id, score DESC);
@@ -0,0 +1,30 @@
+CREATE TYPE security_event_type AS ENUM (
+    'register', 'login', 'two_factor', 'logout', 'refresh', 'password_change'
+);
+CREATE TYPE security_event_outcome AS ENUM ('success', 'failure');
+
+CREATE TABLE public.security_event (
+    id UUID PRIMARY KEY,
+    user_id UUID REFERENCES public.user (id),
+    type security_event_type NOT NULL,
+    outcome security_event_outcome NOT NULL,
+    identifier TEXT,
+    ip TEXT,
+    user_agent TEXT,
+    detail TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX security_event_user_id_created_at_idx ON public.security_event (user_id, created_at);
+CREATE INDEX security_event_created_at_idx ON public.security_event (created_at);
+
+-- 감사 로그는 추가만 가능
+CREATE FUNCTION public.reject_security_event_change() RETURNS TRIGGER AS $$
+BEGIN
+    RAISE EXCEPTION 'security_event is append-only';
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER security_event_append_only
+    BEFORE UPDATE OR DELETE ON public.security_event
+    FOR EACH ROW EXECUTE FUNCTION public.reject_security_event_change();
@@ -0,0 +1,26 @@
+CREATE TABLE public.conversation (
+    id UUID PRIMARY KEY,
+    direct_key TEXT UNIQUE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    last_message_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE public.conversation_member (
+    conversation_id UUID NOT NULL REFERENCES public.conversation (id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES public.user (id) ON DELETE CASCADE,
+    joined_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (conversation_id, user_id)
+);
+
+CREATE INDEX conversation_member_user_id_idx ON public.conversation_member (user_id);
+
+CREATE TABLE public.chat_message (
+    id UUID PRIMARY KEY,
+    conversation_id UUID NOT NULL REFERENCES public.conversation (id) ON DELETE CASCADE,
+    sender_id UUID NOT NULL REFERENCES public.user (id),
+    message TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX chat_message_conversation_id_created_at_idx
+    ON public.chat_message (conversation_id, created_at);
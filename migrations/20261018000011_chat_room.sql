@@ -0,0 +1,19 @@
+CREATE TYPE conversation_type AS ENUM ('direct', 'room');
+CREATE TYPE room_member_role AS ENUM ('owner', 'moderator', 'member');
+
+ALTER TABLE public.conversation
+    ADD COLUMN type conversation_type NOT NULL DEFAULT 'direct',
+    ADD COLUMN name TEXT,
+    ADD COLUMN is_public BOOLEAN NOT NULL DEFAULT FALSE,
+    ADD COLUMN game_id UUID UNIQUE REFERENCES public.game (id) ON DELETE CASCADE;
+
+ALTER TABLE public.conversation_member
+    ADD COLUMN role room_member_role NOT NULL DEFAULT 'member';
+
+CREATE TABLE public.conversation_invite (
+    conversation_id UUID NOT NULL REFERENCES public.conversation (id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES public.user (id) ON DELETE CASCADE,
+    invited_by UUID NOT NULL REFERENCES public.user (id) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (conversation_id, user_id)
+);
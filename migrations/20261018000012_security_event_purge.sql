@@ -0,0 +1,20 @@
+-- 계정을 영구 삭제할 때 개인정보 필드를 비우는 변경만 허용
+CREATE OR REPLACE FUNCTION public.reject_security_event_change() RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP = 'UPDATE'
+        AND NEW.id = OLD.id
+        AND NEW.user_id IS NOT DISTINCT FROM OLD.user_id
+        AND NEW.type = OLD.type
+        AND NEW.outcome = OLD.outcome
+        AND NEW.detail IS NOT DISTINCT FROM OLD.detail
+        AND NEW.created_at = OLD.created_at
+        AND NEW.identifier IS NULL
+        AND NEW.ip IS NULL
+        AND NEW.user_agent IS NULL
+    THEN
+        RETURN NEW;
+    END IF;
+
+    RAISE EXCEPTION 'security_event is append-only';
+END;
+$$ LANGUAGE plpgsql;
@@ -0,0 +1,7 @@
+CREATE TABLE public.conversation_ban (
+    conversation_id UUID NOT NULL REFERENCES public.conversation (id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES public.user (id) ON DELETE CASCADE,
+    banned_by UUID NOT NULL REFERENCES public.user (id) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (conversation_id, user_id)
+);
@@ -0,0 +1,21 @@
+-- 게임이 추가될 때 로비를 함께 생성
+CREATE FUNCTION public.create_game_lobby() RETURNS TRIGGER AS $$
+BEGIN
+    INSERT INTO public.conversation (id, type, is_public, game_id)
+    VALUES (gen_random_uuid(), 'room', TRUE, NEW.id)
+    ON CONFLICT (game_id) DO NOTHING;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER game_create_lobby
+    AFTER INSERT ON public.game
+    FOR EACH ROW EXECUTE FUNCTION public.create_game_lobby();
+
+-- 이미 존재하는 게임의 로비
+INSERT INTO public.conversation (id, type, is_public, game_id)
+SELECT gen_random_uuid(), 'room', TRUE, g.id
+FROM public.game g
+WHERE NOT EXISTS (
+    SELECT 1 FROM public.conversation c WHERE c.game_id = g.id
+);
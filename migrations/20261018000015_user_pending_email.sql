@@ -0,0 +1,3 @@
+-- 인증을 마칠 때까지 보류 중인 새 이메일 주소
+ALTER TABLE public.user
+    ADD COLUMN pending_email TEXT;
@@ -0,0 +1,263 @@
+use std::time::Duration as StdDuration;
+
+use chrono::{DateTime, Duration, Utc};
+use sqlx::{PgPool, Postgres, Transaction};
+use uuid::Uuid;
+use webgame_collection_api_macros::Error;
+
+use crate::config::CONFIG;
+
+use super::{guest::purge_inactive_guests, refresh::revoke_user_refresh_tokens};
+
+#[derive(Error)]
+pub enum AccountError {
+    #[error(message = "Database error")]
+    DbError(sqlx::Error),
+    #[error(message = "Redis error")]
+    RedisError(redis::RedisError),
+    #[error(message = "User not found")]
+    UserNotFound,
+}
+
+pub fn deletion_cutoff() -> DateTime<Utc> {
+    Utc::now() - Duration::seconds(CONFIG.account_deletion_grace_period)
+}
+
+pub fn is_restorable(deleted_at: &DateTime<Utc>) -> bool {
+    *deleted_at > deletion_cutoff()
+}
+
+pub async fn delete_account(
+    pool: &PgPool,
+    redis_conn: &mut deadpool_redis::Connection,
+    user_id: &Uuid,
+) -> Result<(), AccountError> {
+    sqlx::query!(
+        r#"
+        UPDATE public.user
+        SET deleted_at = CURRENT_TIMESTAMP
+        WHERE id = $1 AND deleted_at IS NULL
+        RETURNING id
+        "#,
+        user_id,
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(AccountError::DbError)?
+    .ok_or(AccountError::UserNotFound)?;
+
+    revoke_user_refresh_tokens(user_id, redis_conn)
+        .await
+        .map_err(AccountError::RedisError)
+}
+
+pub async fn restore_account(pool: &PgPool, user_id: &Uuid) -> Result<bool, sqlx::Error> {
+    sqlx::query!(
+        r#"
+        UPDATE public.user
+        SET deleted_at = NULL
+        WHERE id = $1 AND deleted_at > $2 AND purged_at IS NULL
+        RETURNING id
+        "#,
+        user_id,
+        deletion_cutoff(),
+    )
+    .fetch_optional(pool)
+    .await
+    .map(|user| user.is_some())
+}
+
+// 유예 기간이 지난 계정은 다른 데이터가 참조할 수 있도록 row는 남겨두고
+// 개인정보와 로그인 수단, 유저가 남긴 데이터를 제거
+pub async fn purge_deleted_accounts(pool: &PgPool) -> Result<Vec<Uuid>, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let user_ids = sqlx::query!(
+        r#"
+        UPDATE public.user
+        SET nickname = 'deleted-' || id::text,
+            email = id::text || '@deleted.invalid',
+            email_verified_at = NULL,
+            pending_email = NULL,
+            avatar_url = NULL,
+            bio = NULL,
+            purged_at = CURRENT_TIMESTAMP
+        WHERE deleted_at <= $1 AND purged_at IS NULL
+        RETURNING id
+        "#,
+        deletion_cutoff(),
+    )
+    .fetch_all(&mut tx)
+    .await?
+    .into_iter()
+    .map(|user| user.id)
+    .collect::<Vec<_>>();
+
+    purge_user_data(&mut tx, &user_ids).await?;
+
+    tx.commit().await?;
+
+    Ok(user_ids)
+}
+
+pub async fn purge_user_data(
+    tx: &mut Transaction<'_, Postgres>,
+    user_ids: &[Uuid],
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        DELETE FROM public.user_auth_method
+        WHERE user_id = ANY($1)
+        "#,
+        user_ids,
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query!(
+        r#"
+        DELETE FROM public.user_two_factor
+        WHERE user_id = ANY($1)
+        "#,
+        user_ids,
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query!(
+        r#"
+        DELETE FROM public.api_key
+        WHERE user_id = ANY($1)
+        "#,
+        user_ids,
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query!(
+        r#"
+        DELETE FROM public.game_consent
+        WHERE user_id = ANY($1)
+        "#,
+        user_ids,
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query!(
+        r#"
+        DELETE FROM public.game_score
+        WHERE user_id = ANY($1)
+        "#,
+        user_ids,
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    // 감사 로그는 남기되 유저를 식별할 수 있는 정보만 비움
+    sqlx::query!(
+        r#"
+        UPDATE public.security_event
+        SET identifier = NULL, ip = NULL, user_agent = NULL
+        WHERE user_id = ANY($1)
+            AND (identifier IS NOT NULL OR ip IS NOT NULL OR user_agent IS NOT NULL)
+        "#,
+        user_ids,
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    // 보낸 메시지는 다른 참여자의 대화 기록이기도 하므로 지우지 않고,
+    // 보낸 사람은 익명화된 유저 row를 가리키도록 남겨 둠
+
+    sqlx::query!(
+        r#"
+        DELETE FROM public.conversation_invite
+        WHERE user_id = ANY($1) OR invited_by = ANY($1)
+        "#,
+        user_ids,
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query!(
+        r#"
+        DELETE FROM public.conversation_ban
+        WHERE user_id = ANY($1) OR banned_by = ANY($1)
+        "#,
+        user_ids,
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    // 방장이 삭제되는 방은 남은 멤버 중 모더레이터, 가장 먼저 참여한 멤버 순으로 방장을 넘김
+    sqlx::query!(
+        r#"
+        UPDATE public.conversation_member
+        SET role = 'owner'
+        WHERE (conversation_id, user_id) IN (
+            SELECT DISTINCT ON (m.conversation_id) m.conversation_id, m.user_id
+            FROM public.conversation_member m
+            JOIN public.conversation_member o
+                ON o.conversation_id = m.conversation_id AND o.role = 'owner'
+            WHERE o.user_id = ANY($1) AND m.user_id <> ALL($1)
+            ORDER BY m.conversation_id, (m.role = 'moderator') DESC, m.joined_at ASC
+        )
+        "#,
+        user_ids,
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query!(
+        r#"
+        DELETE FROM public.conversation_member
+        WHERE user_id = ANY($1)
+        "#,
+        user_ids,
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    Ok(())
+}
+
+// 세션을 폐기하면 해당 세션에서 발급된 access token도 함께 무효화됨
+async fn revoke_sessions(redis_pool: &deadpool_redis::Pool, user_ids: &[Uuid]) {
+    let mut redis_conn = match redis_pool.get().await {
+        Ok(redis_conn) => redis_conn,
+        Err(e) => return log::error!("Failed to revoke sessions of purged users: {}", e),
+    };
+
+    for user_id in user_ids {
+        if let Err(e) = revoke_user_refresh_tokens(user_id, &mut redis_conn).await {
+            log::error!("Failed to revoke sessions of {}: {}", user_id, e);
+        }
+    }
+}
+
+pub async fn run_purge_job(pool: PgPool, redis_pool: deadpool_redis::Pool) {
+    let mut interval = tokio::time::interval(StdDuration::from_secs(CONFIG.account_purge_interval));
+
+    loop {
+        interval.tick().await;
+
+        match purge_deleted_accounts(&pool).await {
+            Ok(user_ids) if !user_ids.is_empty() => {
+                revoke_sessions(&redis_pool, &user_ids).await;
+                log::info!("Purged {} deleted accounts", user_ids.len())
+            }
+            Ok(_) => {}
+            Err(e) => log::error!("Failed to purge deleted accounts: {}", e),
+        }
+
+        match purge_inactive_guests(&pool).await {
+            Ok(user_ids) if !user_ids.is_empty() => {
+                revoke_sessions(&redis_pool, &user_ids).await;
+                log::info!("Purged {} inactive guests", user_ids.len())
+            }
+            Ok(_) => {}
+            Err(e) => log::error!("Failed to purge inactive guests: {}", e),
+        }
+    }
+}
@@ -0,0 +1,179 @@
+use chrono::{DateTime, Utc};
+use ring::digest;
+use sqlx::PgPool;
+use uuid::Uuid;
+use webgame_collection_api_macros::Error;
+
+use super::{create_random_token, scope::Scope};
+
+const API_KEY_PREFIX: &str = "wgc_";
+const API_KEY_DISPLAY_PREFIX_LEN: usize = 8;
+
+pub struct ApiKeyData {
+    pub id: Uuid,
+    pub name: String,
+    pub prefix: String,
+    pub scopes: Vec<Scope>,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
+pub struct ApiKeyOwner {
+    pub user_id: Uuid,
+    pub scopes: Vec<Scope>,
+}
+
+#[derive(Error)]
+pub enum ApiKeyError {
+    #[error(message = "Database error")]
+    DbError(sqlx::Error),
+    #[error(message = "Key creation failed")]
+    KeyCreationFailed,
+    #[error(message = "At least one scope is required")]
+    ScopesNotProvided,
+    #[error(message = "Expiry must be in the future")]
+    InvalidExpiry,
+    #[error(message = "API key not found")]
+    KeyNotFound,
+}
+
+// 평문 키는 생성 시점에만 반환되고 DB에는 해시만 저장됨
+pub async fn create_api_key(
+    pool: &PgPool,
+    user_id: &Uuid,
+    name: String,
+    scopes: Vec<Scope>,
+    expires_at: Option<DateTime<Utc>>,
+) -> Result<(ApiKeyData, String), ApiKeyError> {
+    if scopes.is_empty() {
+        return Err(ApiKeyError::ScopesNotProvided);
+    }
+    if matches!(expires_at, Some(expires_at) if expires_at <= Utc::now()) {
+        return Err(ApiKeyError::InvalidExpiry);
+    }
+
+    let key = create_random_token()
+        .map(|token| format!("{}{}", API_KEY_PREFIX, token))
+        .ok_or(ApiKeyError::KeyCreationFailed)?;
+    let prefix = key[..API_KEY_PREFIX.len() + API_KEY_DISPLAY_PREFIX_LEN].to_owned();
+    let scope_names = scopes.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+
+    let record = sqlx::query!(
+        r#"
+        INSERT INTO public.api_key (id, user_id, name, prefix, key_hash, scopes, expires_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        RETURNING id, name, prefix, created_at, expires_at
+        "#,
+        Uuid::new_v4(),
+        user_id,
+        name,
+        prefix,
+        hash_api_key(&key),
+        &scope_names,
+        expires_at,
+    )
+    .fetch_one(pool)
+    .await
+    .map_err(ApiKeyError::DbError)?;
+
+    Ok((
+        ApiKeyData {
+            id: record.id,
+            name: record.name,
+            prefix: record.prefix,
+            scopes,
+            created_at: record.created_at,
+            expires_at: record.expires_at,
+            last_used_at: None,
+        },
+        key,
+    ))
+}
+
+pub async fn list_api_keys(pool: &PgPool, user_id: &Uuid) -> Result<Vec<ApiKeyData>, ApiKeyError> {
+    let records = sqlx::query!(
+        r#"
+        SELECT id, name, prefix, scopes, created_at, expires_at, last_used_at
+        FROM public.api_key
+        WHERE user_id = $1 AND revoked_at IS NULL
+        ORDER BY created_at DESC
+        "#,
+        user_id,
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(ApiKeyError::DbError)?;
+
+    Ok(records
+        .into_iter()
+        .map(|record| ApiKeyData {
+            id: record.id,
+            name: record.name,
+            prefix: record.prefix,
+            scopes: parse_scopes(&record.scopes),
+            created_at: record.created_at,
+            expires_at: record.expires_at,
+            last_used_at: record.last_used_at,
+        })
+        .collect())
+}
+
+pub async fn revoke_api_key(
+    pool: &PgPool,
+    user_id: &Uuid,
+    key_id: &Uuid,
+) -> Result<(), ApiKeyError> {
+    sqlx::query!(
+        r#"
+        UPDATE public.api_key
+        SET revoked_at = CURRENT_TIMESTAMP
+        WHERE id = $1 AND user_id = $2 AND revoked_at IS NULL
+        RETURNING id
+        "#,
+        key_id,
+        user_id,
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(ApiKeyError::DbError)?
+    .ok_or(ApiKeyError::KeyNotFound)
+    .map(|_| ())
+}
+
+pub async fn resolve_api_key(pool: &PgPool, key: &str) -> Result<Option<ApiKeyOwner>, sqlx::Error> {
+    if !key.starts_with(API_KEY_PREFIX) {
+        return Ok(None);
+    }
+
+    let record = sqlx::query!(
+        r#"
+        UPDATE public.api_key k
+        SET last_used_at = CURRENT_TIMESTAMP
+        FROM public.user u
+        WHERE k.key_hash = $1
+            AND k.revoked_at IS NULL
+            AND (k.expires_at IS NULL OR k.expires_at > CURRENT_TIMESTAMP)
+            AND u.id = k.user_id
+            AND u.deleted_at IS NULL
+        RETURNING k.user_id, k.scopes
+        "#,
+        hash_api_key(key),
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(record.map(|record| ApiKeyOwner {
+        user_id: record.user_id,
+        scopes: parse_scopes(&record.scopes),
+    }))
+}
+
+fn parse_scopes(scopes: &[String]) -> Vec<Scope> {
+    scopes.iter().filter_map(|s| s.parse().ok()).collect()
+}
+
+// 키 자체가 충분히 무작위이므로 솔트 없이 SHA-256만으로 저장
+fn hash_api_key(key: &str) -> String {
+    base64::encode(digest::digest(&digest::SHA256, key.as_bytes()))
+}
@@ -0,0 +1,92 @@
+use async_graphql::Enum;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use super::client_info::ClientInfo;
+
+#[derive(sqlx::Type, Enum, Copy, Clone, Eq, PartialEq, Debug)]
+#[sqlx(type_name = "security_event_type", rename_all = "snake_case")]
+pub enum SecurityEventType {
+    Register,
+    Login,
+    TwoFactor,
+    Logout,
+    Refresh,
+    PasswordChange,
+}
+
+#[derive(sqlx::Type, Enum, Copy, Clone, Eq, PartialEq, Debug)]
+#[sqlx(type_name = "security_event_outcome", rename_all = "lowercase")]
+pub enum SecurityEventOutcome {
+    Success,
+    Failure,
+}
+
+pub struct NewSecurityEvent {
+    pub ty: SecurityEventType,
+    pub outcome: SecurityEventOutcome,
+    pub user_id: Option<Uuid>,
+    pub identifier: Option<String>,
+    pub detail: Option<String>,
+}
+
+impl NewSecurityEvent {
+    pub fn success(ty: SecurityEventType, user_id: Uuid) -> NewSecurityEvent {
+        NewSecurityEvent {
+            ty,
+            outcome: SecurityEventOutcome::Success,
+            user_id: Some(user_id),
+            identifier: None,
+            detail: None,
+        }
+    }
+
+    pub fn failure(ty: SecurityEventType, detail: String) -> NewSecurityEvent {
+        NewSecurityEvent {
+            ty,
+            outcome: SecurityEventOutcome::Failure,
+            user_id: None,
+            identifier: None,
+            detail: Some(detail),
+        }
+    }
+
+    pub fn user_id(mut self, user_id: Option<Uuid>) -> NewSecurityEvent {
+        self.user_id = user_id;
+        self
+    }
+
+    pub fn identifier(mut self, identifier: String) -> NewSecurityEvent {
+        self.identifier = Some(identifier);
+        self
+    }
+}
+
+// 감사 로그 기록 실패로 인증 요청 자체가 실패하지 않도록 에러는 로그로만 남김
+pub async fn record_security_event(
+    pool: &PgPool,
+    client_info: &ClientInfo,
+    event: NewSecurityEvent,
+) {
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO public.security_event
+            (id, user_id, type, outcome, identifier, ip, user_agent, detail)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+        "#,
+        Uuid::new_v4(),
+        event.user_id,
+        event.ty as SecurityEventType,
+        event.outcome as SecurityEventOutcome,
+        event.identifier,
+        client_info.ip,
+        client_info.user_agent,
+        event.detail,
+    )
+    .execute(pool)
+    .await;
+
+    if let Err(e) = result {
+        log::warn!("Failed to record a security event: {}", e);
+    }
+}
@@ -1,81 +1,229 @@
 use std::{convert::Infallible, pin::Pin, str::FromStr};
 
-use actix_web::{dev::Payload, FromRequest, HttpRequest};
+use actix_web::{dev::Payload, web, FromRequest, HttpRequest};
+use chrono::Utc;
 use futures::Future;
-use jsonwebtoken::{Algorithm, DecodingKey, Validation};
+use sqlx::PgPool;
 use uuid::Uuid;
 use webgame_collection_api_macros::Error;
 
-use crate::{auth::login::Claims, config::CONFIG};
+use crate::auth::{
+    api_key::{resolve_api_key, ApiKeyOwner},
+    jwt::JWT_KEYS,
+    login::Claims,
+    role::Role,
+    scope::Scope,
+};
 
-use super::get_invalid_token_key;
+use super::{get_invalid_token_key, session::session_exists};
 
+#[derive(Clone)]
 pub struct AuthInfo {
     user_id: Option<Uuid>,
-    auth_token: Option<String>,
+    session_id: Option<Uuid>,
+    roles: Vec<Role>,
+    // 범위가 제한된 자격 증명(API 키 등)인 경우에만 Some
+    scopes: Option<Vec<Scope>>,
+    // 게임에 위임된 토큰인 경우 해당 게임의 ID
+    game_id: Option<Uuid>,
+    token_id: Option<String>,
     token_exp: Option<usize>,
+    // API 키로 인증한 경우 연결 중에 다시 확인하기 위해 보관
+    api_key: Option<String>,
     valid: Option<bool>,
 }
 
 impl AuthInfo {
     pub fn get_user_id(&self) -> Result<Uuid, AuthError> {
         if self.valid.unwrap_or(false) {
-            self.user_id.ok_or(AuthError::NotAuthorized)
+            match self.scopes {
+                Some(_) => Err(AuthError::InsufficientScope),
+                None => self.user_id.ok_or(AuthError::NotAuthorized),
+            }
         } else {
             Err(AuthError::Invalidated)
         }
     }
 
+    // 범위가 제한된 자격 증명도 해당 범위를 가지고 있다면 허용
+    pub fn get_user_id_for(&self, scope: Scope) -> Result<Uuid, AuthError> {
+        if self.valid.unwrap_or(false) {
+            match &self.scopes {
+                Some(scopes) if !scopes.contains(&scope) => Err(AuthError::InsufficientScope),
+                // 게임에 위임된 토큰은 게임과 관련된 범위로만 사용 가능
+                _ if self.game_id.is_some() && !scope.is_delegable() => {
+                    Err(AuthError::InsufficientScope)
+                }
+                _ => self.user_id.ok_or(AuthError::NotAuthorized),
+            }
+        } else {
+            Err(AuthError::Invalidated)
+        }
+    }
+
+    // 위임된 토큰은 발급 대상 게임에 대해서만 사용 가능
+    pub fn get_user_id_for_game(&self, game_id: &Uuid, scope: Scope) -> Result<Uuid, AuthError> {
+        match self.game_id {
+            Some(ref delegated_game_id) if delegated_game_id != game_id => {
+                Err(AuthError::InsufficientScope)
+            }
+            _ => self.get_user_id_for(scope),
+        }
+    }
+
+    pub fn is_authenticated(&self) -> bool {
+        self.valid.unwrap_or(false) && self.user_id.is_some()
+    }
+
+    pub fn is_delegated(&self) -> bool {
+        self.game_id.is_some()
+    }
+
+    pub fn session_id(&self) -> Option<Uuid> {
+        self.session_id
+    }
+
+    // 검증 여부와 관계없이 자격 증명에 담긴 유저 ID
+    pub fn claimed_user_id(&self) -> Option<Uuid> {
+        self.user_id
+    }
+
+    // 관리자는 모든 역할의 권한을 가짐
+    pub fn has_role(&self, role: Role) -> bool {
+        self.roles.contains(&Role::Admin) || self.roles.contains(&role)
+    }
+
+    pub fn check_role(&self, role: Role) -> Result<(), AuthError> {
+        self.get_user_id()?;
+
+        match self.has_role(role) {
+            true => Ok(()),
+            false => Err(AuthError::Forbidden),
+        }
+    }
+
+    pub fn check_owner(&self, owner_id: &Uuid) -> Result<(), AuthError> {
+        match self.get_user_id()? {
+            user_id if &user_id == owner_id => Ok(()),
+            _ => Err(AuthError::Forbidden),
+        }
+    }
+
+    pub fn check_owner_or_admin(&self, owner_id: &Uuid) -> Result<(), AuthError> {
+        match self.check_owner(owner_id) {
+            Err(AuthError::Forbidden) if self.has_role(Role::Admin) => Ok(()),
+            result => result,
+        }
+    }
+
     pub fn from_header(header: Option<String>) -> AuthInfo {
         match header {
             Some(header) if header.starts_with("Bearer ") => {
                 let token = header.trim_start_matches("Bearer ");
-                let decode_data = jsonwebtoken::decode::<Claims>(
-                    token,
-                    &DecodingKey::from_secret(CONFIG.jwt_secret.as_slice()),
-                    &Validation::new(Algorithm::HS512),
-                )
-                .map_err(anyhow::Error::new)
-                .and_then(|data| {
-                    Ok((
-                        Uuid::from_str(&data.claims.sub).map_err(anyhow::Error::new)?,
-                        data.claims.exp,
-                    ))
-                });
-                let (user_id, token_exp) = match decode_data {
-                    Ok((user_id, token_exp)) => (Some(user_id), Some(token_exp)),
-                    Err(_) => (None, None),
-                };
+                let decode_data = JWT_KEYS
+                    .decode::<Claims>(token)
+                    .map_err(anyhow::Error::new)
+                    .and_then(|claims| {
+                        Ok((
+                            Uuid::from_str(&claims.sub).map_err(anyhow::Error::new)?,
+                            claims
+                                .sid
+                                .map(|sid| Uuid::from_str(&sid))
+                                .transpose()
+                                .map_err(anyhow::Error::new)?,
+                            claims.roles,
+                            claims.scp,
+                            claims
+                                .gid
+                                .map(|gid| Uuid::from_str(&gid))
+                                .transpose()
+                                .map_err(anyhow::Error::new)?,
+                            claims.jti,
+                            claims.exp,
+                        ))
+                    });
+                let (user_id, session_id, roles, scopes, game_id, token_id, token_exp) =
+                    match decode_data {
+                        Ok((user_id, session_id, roles, scopes, game_id, token_id, token_exp)) => (
+                            Some(user_id),
+                            session_id,
+                            roles,
+                            scopes,
+                            game_id,
+                            Some(token_id),
+                            Some(token_exp),
+                        ),
+                        Err(_) => (None, None, vec![], None, None, None, None),
+                    };
 
                 AuthInfo {
                     user_id,
-                    auth_token: Some(token.to_owned()),
+                    session_id,
+                    roles,
+                    scopes,
+                    game_id,
+                    token_id,
                     token_exp,
+                    api_key: None,
                     valid: None,
                 }
             }
-            _ => AuthInfo {
-                user_id: None,
-                auth_token: None,
+            _ => AuthInfo::anonymous(),
+        }
+    }
+
+    pub async fn from_api_key(pool: &PgPool, key: &str) -> AuthInfo {
+        match resolve_api_key(pool, key).await {
+            Ok(Some(ApiKeyOwner { user_id, scopes })) => AuthInfo {
+                user_id: Some(user_id),
+                session_id: None,
+                roles: vec![],
+                scopes: Some(scopes),
+                game_id: None,
+                token_id: None,
                 token_exp: None,
-                valid: None,
+                api_key: Some(key.to_owned()),
+                valid: Some(true),
+            },
+            _ => AuthInfo {
+                valid: Some(false),
+                ..AuthInfo::anonymous()
             },
         }
     }
 
+    fn anonymous() -> AuthInfo {
+        AuthInfo {
+            user_id: None,
+            session_id: None,
+            roles: vec![],
+            scopes: None,
+            game_id: None,
+            token_id: None,
+            token_exp: None,
+            api_key: None,
+            valid: None,
+        }
+    }
+
     pub async fn verify(&mut self, redis_conn: &mut deadpool_redis::Connection) -> bool {
         if let Some(valid) = self.valid {
             return valid;
         }
 
-        let valid = match &self.auth_token {
-            Some(auth_token) => {
-                let result = redis::cmd("GET")
-                    .arg(get_invalid_token_key(auth_token))
-                    .query_async::<_, Option<i64>>(redis_conn)
+        let valid = match &self.token_id {
+            Some(token_id) => {
+                let invalidated = redis::cmd("EXISTS")
+                    .arg(get_invalid_token_key(token_id))
+                    .query_async::<_, bool>(redis_conn)
                     .await;
+                // 세션이 폐기된 경우 해당 세션에서 발급된 access token도 함께 무효
+                let session_alive = match &self.session_id {
+                    Some(session_id) => session_exists(session_id, redis_conn).await,
+                    None => Ok(true),
+                };
 
-                result.map(|opt| opt.is_none()).unwrap_or(false)
+                matches!((invalidated, session_alive), (Ok(false), Ok(true)))
             }
             None => false,
         };
@@ -84,12 +232,48 @@ impl AuthInfo {
         valid
     }
 
+    // 오래 유지되는 연결에서 토큰이 만료되었거나 폐기되었는지 다시 확인
+    pub async fn recheck(
+        &mut self,
+        pool: &PgPool,
+        redis_conn: &mut deadpool_redis::Connection,
+    ) -> bool {
+        // API 키는 폐기나 만료, 범위 변경을 DB에서 다시 확인
+        if let Some(api_key) = &self.api_key {
+            let valid = match resolve_api_key(pool, api_key).await {
+                Ok(Some(ApiKeyOwner { user_id, scopes })) if Some(user_id) == self.user_id => {
+                    self.scopes = Some(scopes);
+                    true
+                }
+                Ok(_) => false,
+                // DB에 접근할 수 없는 경우 이전 결과를 유지
+                Err(_) => return self.valid.unwrap_or(false),
+            };
+
+            self.valid = Some(valid);
+            return valid;
+        }
+
+        if self.token_id.is_none() {
+            return self.valid.unwrap_or(false);
+        }
+
+        let expired = matches!(self.token_exp, Some(exp) if exp <= Utc::now().timestamp() as usize);
+        if expired {
+            self.valid = Some(false);
+            return false;
+        }
+
+        self.valid = None;
+        self.verify(redis_conn).await
+    }
+
     pub async fn invalidate(
         &self, // &mut self
         redis_conn: &mut deadpool_redis::Connection,
     ) -> Result<bool, redis::RedisError> {
-        if let (Some(auth_token), Some(token_exp)) = (&self.auth_token, &self.token_exp) {
-            let key = get_invalid_token_key(auth_token);
+        if let (Some(token_id), Some(token_exp)) = (&self.token_id, &self.token_exp) {
+            let key = get_invalid_token_key(token_id);
             let token_exp = token_exp.to_string();
 
             return redis::pipe()
@@ -120,12 +304,26 @@ impl FromRequest for AuthInfo {
     type Future = Pin<Box<dyn Future<Output = Result<AuthInfo, Infallible>>>>;
 
     fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
-        let auth_header = req
-            .headers()
-            .get("Authorization")
-            .and_then(|h| h.to_str().ok().map(|s| s.to_string()));
+        let header = |name: &str| {
+            req.headers()
+                .get(name)
+                .and_then(|h| h.to_str().ok().map(|s| s.to_string()))
+        };
+        let auth_header = header("Authorization");
+        let api_key = header("X-Api-Key").or_else(|| {
+            auth_header
+                .as_deref()
+                .and_then(|h| h.strip_prefix("ApiKey "))
+                .map(|key| key.to_owned())
+        });
+        let pool = req.app_data::<web::Data<PgPool>>().cloned();
 
-        Box::pin(async move { Ok(AuthInfo::from_header(auth_header)) })
+        Box::pin(async move {
+            match (api_key, pool) {
+                (Some(api_key), Some(pool)) => Ok(AuthInfo::from_api_key(&pool, &api_key).await),
+                _ => Ok(AuthInfo::from_header(auth_header)),
+            }
+        })
     }
 }
 
@@ -135,4 +333,8 @@ pub enum AuthError {
     NotAuthorized,
     #[error(message = "Invalidated auth token")]
     Invalidated,
+    #[error(message = "Access to the resource is forbidden")]
+    Forbidden,
+    #[error(message = "The credential does not grant the required scope")]
+    InsufficientScope,
 }
@@ -0,0 +1,62 @@
+use std::{convert::Infallible, net::IpAddr};
+
+use actix_web::{dev::Payload, FromRequest, HttpRequest};
+use futures::future::{ready, Ready};
+
+use crate::config::CONFIG;
+
+#[derive(Clone, Default)]
+pub struct ClientInfo {
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+}
+
+impl ClientInfo {
+    pub fn from_http_request(req: &HttpRequest) -> ClientInfo {
+        let ip = req
+            .peer_addr()
+            .map(|addr| client_ip(req, addr.ip()).to_string());
+        let user_agent = req
+            .headers()
+            .get("User-Agent")
+            .and_then(|h| h.to_str().ok().map(|s| s.to_string()));
+
+        ClientInfo { ip, user_agent }
+    }
+}
+
+// 신뢰하는 프록시를 거친 경우에만 X-Forwarded-For를 오른쪽부터 따라가며 실제 IP를 찾음
+fn client_ip(req: &HttpRequest, peer_ip: IpAddr) -> IpAddr {
+    let is_trusted = |ip: &IpAddr| CONFIG.trusted_proxies.contains(ip);
+    if !is_trusted(&peer_ip) {
+        return peer_ip;
+    }
+
+    let forwarded = req
+        .headers()
+        .get_all("X-Forwarded-For")
+        .filter_map(|h| h.to_str().ok())
+        .flat_map(|h| h.split(','))
+        .map(|addr| addr.trim().parse::<IpAddr>())
+        .collect::<Vec<_>>();
+
+    let mut ip = peer_ip;
+    for addr in forwarded.into_iter().rev() {
+        match addr {
+            Ok(addr) if is_trusted(&ip) => ip = addr,
+            _ => break,
+        }
+    }
+
+    ip
+}
+
+impl FromRequest for ClientInfo {
+    type Error = Infallible;
+    type Config = ();
+    type Future = Ready<Result<ClientInfo, Infallible>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        ready(Ok(ClientInfo::from_http_request(req)))
+    }
+}
@@ -0,0 +1,234 @@
+use std::{sync::Arc, time::Duration};
+
+use async_graphql::{
+    extensions::{Extension, ExtensionContext, ExtensionFactory, NextPrepareRequest},
+    Request, ServerResult,
+};
+use async_trait::async_trait;
+use sqlx::PgPool;
+use tokio::sync::RwLock;
+use webgame_collection_api_macros::Error;
+
+use crate::config::CONFIG;
+
+use super::{auth_info::AuthInfo, scope::Scope};
+
+struct ConnectionAuthState {
+    auth_info: AuthInfo,
+    // 연결에서 시작된 구독들이 필요로 하는 범위
+    required_scopes: Vec<Scope>,
+}
+
+// WebSocket 연결 동안 공유되는 인증 상태로, 연결을 유지한 채 토큰을 교체할 수 있음
+#[derive(Clone)]
+pub struct ConnectionAuth {
+    inner: Arc<RwLock<ConnectionAuthState>>,
+}
+
+#[derive(Error)]
+pub enum ConnectionAuthError {
+    #[error(message = "Redis error")]
+    RedisError(deadpool_redis::PoolError),
+    #[error(message = "Invalidated auth token")]
+    Invalidated,
+    #[error(message = "The token belongs to a different user")]
+    UserMismatch,
+    #[error(message = "The token lacks a scope required by active subscriptions")]
+    InsufficientScope,
+}
+
+impl ConnectionAuth {
+    pub fn new(auth_info: AuthInfo) -> ConnectionAuth {
+        ConnectionAuth {
+            inner: Arc::new(RwLock::new(ConnectionAuthState {
+                auth_info,
+                required_scopes: vec![],
+            })),
+        }
+    }
+
+    // connection_init에서 받은 자격 증명으로 초기화
+    pub async fn init(&self, auth_info: AuthInfo) {
+        self.inner.write().await.auth_info = auth_info;
+    }
+
+    pub async fn is_authenticated(&self) -> bool {
+        self.inner.read().await.auth_info.is_authenticated()
+    }
+
+    pub async fn require(&self, scope: Scope) {
+        let mut state = self.inner.write().await;
+        if !state.required_scopes.contains(&scope) {
+            state.required_scopes.push(scope);
+        }
+    }
+
+    // 구독이 특정 유저에 묶여 있으므로 같은 유저의 토큰으로만 교체 가능
+    pub async fn replace(
+        &self,
+        redis_pool: &deadpool_redis::Pool,
+        mut auth_info: AuthInfo,
+    ) -> Result<(), ConnectionAuthError> {
+        let mut redis_conn = redis_pool
+            .get()
+            .await
+            .map_err(ConnectionAuthError::RedisError)?;
+        if !auth_info.verify(&mut redis_conn).await {
+            return Err(ConnectionAuthError::Invalidated);
+        }
+
+        let mut state = self.inner.write().await;
+        if state.auth_info.claimed_user_id() != auth_info.claimed_user_id() {
+            return Err(ConnectionAuthError::UserMismatch);
+        }
+        if state
+            .required_scopes
+            .iter()
+            .any(|scope| auth_info.get_user_id_for(*scope).is_err())
+        {
+            return Err(ConnectionAuthError::InsufficientScope);
+        }
+
+        state.auth_info = auth_info;
+        Ok(())
+    }
+
+    pub async fn recheck(&self, pool: &PgPool, redis_pool: &deadpool_redis::Pool) -> bool {
+        match redis_pool.get().await {
+            Ok(mut redis_conn) => {
+                self.inner
+                    .write()
+                    .await
+                    .auth_info
+                    .recheck(pool, &mut redis_conn)
+                    .await
+            }
+            // Redis에 접근할 수 없는 경우 연결을 끊지 않고 다음 확인으로 넘김
+            Err(_) => true,
+        }
+    }
+
+    // 연결에서 실행되는 각 operation은 다시 확인한 자격 증명을 사용
+    pub async fn current(&self, pool: &PgPool, redis_pool: &deadpool_redis::Pool) -> AuthInfo {
+        self.recheck(pool, redis_pool).await;
+        self.inner.read().await.auth_info.clone()
+    }
+
+    // 토큰이 만료되거나 폐기될 때까지 주기적으로 확인
+    pub async fn wait_until_revoked(self, pool: PgPool, redis_pool: deadpool_redis::Pool) {
+        let mut interval =
+            tokio::time::interval(Duration::from_secs(CONFIG.ws_auth_check_interval));
+
+        loop {
+            interval.tick().await;
+            if !self.recheck(&pool, &redis_pool).await {
+                return;
+            }
+        }
+    }
+}
+
+// WebSocket 연결의 operation마다 ConnectionAuth로부터 AuthInfo를 새로 넣어줌
+pub struct ConnectionAuthExtension;
+
+impl ExtensionFactory for ConnectionAuthExtension {
+    fn create(&self) -> Arc<dyn Extension> {
+        Arc::new(ConnectionAuthExtension)
+    }
+}
+
+#[async_trait]
+impl Extension for ConnectionAuthExtension {
+    async fn prepare_request(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        request: Request,
+        next: NextPrepareRequest<'_>,
+    ) -> ServerResult<Request> {
+        let request = match (
+            ctx.data_opt::<ConnectionAuth>(),
+            ctx.data_opt::<PgPool>(),
+            ctx.data_opt::<deadpool_redis::Pool>(),
+        ) {
+            (Some(connection_auth), Some(pool), Some(redis_pool)) => {
+                request.data(connection_auth.current(pool, redis_pool).await)
+            }
+            _ => request,
+        };
+
+        next.run(ctx, request).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::auth::{jwt::JWT_KEYS, login::Claims};
+
+    fn access_token(user_id: &Uuid) -> AuthInfo {
+        let token = JWT_KEYS
+            .encode(&Claims::new(user_id, None).unwrap())
+            .unwrap();
+        AuthInfo::from_header(Some(format!("Bearer {}", token)))
+    }
+
+    async fn connection_auth(redis_pool: &deadpool_redis::Pool, user_id: &Uuid) -> ConnectionAuth {
+        let mut auth_info = access_token(user_id);
+        assert!(auth_info.verify(&mut redis_pool.get().await.unwrap()).await);
+        ConnectionAuth::new(auth_info)
+    }
+
+    // Redis가 필요하므로 `cargo test -- --ignored`로 실행
+    #[tokio::test]
+    #[ignore]
+    async fn replaces_token_of_same_user() {
+        let postgres_pool = PgPool::connect_lazy(&CONFIG.database_url).unwrap();
+        let redis_pool = CONFIG.redis.create_pool().unwrap();
+        let user_id = Uuid::new_v4();
+        let connection_auth = connection_auth(&redis_pool, &user_id).await;
+
+        let replacement = access_token(&user_id);
+        assert!(connection_auth
+            .replace(&redis_pool, replacement.clone())
+            .await
+            .is_ok());
+
+        // 교체된 토큰이 폐기되면 연결도 더 이상 유효하지 않음
+        replacement
+            .invalidate(&mut redis_pool.get().await.unwrap())
+            .await
+            .unwrap();
+        assert!(!connection_auth.recheck(&postgres_pool, &redis_pool).await);
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn rejects_token_of_other_user() {
+        let redis_pool = CONFIG.redis.create_pool().unwrap();
+        let connection_auth = connection_auth(&redis_pool, &Uuid::new_v4()).await;
+
+        let result = connection_auth
+            .replace(&redis_pool, access_token(&Uuid::new_v4()))
+            .await;
+        assert!(matches!(result, Err(ConnectionAuthError::UserMismatch)));
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn rejects_revoked_token() {
+        let redis_pool = CONFIG.redis.create_pool().unwrap();
+        let user_id = Uuid::new_v4();
+        let connection_auth = connection_auth(&redis_pool, &user_id).await;
+
+        let replacement = access_token(&user_id);
+        replacement
+            .invalidate(&mut redis_pool.get().await.unwrap())
+            .await
+            .unwrap();
+
+        let result = connection_auth.replace(&redis_pool, replacement).await;
+        assert!(matches!(result, Err(ConnectionAuthError::Invalidated)));
+    }
+}
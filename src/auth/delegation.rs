@@ -0,0 +1,288 @@
+use std::{collections::HashMap, str::FromStr};
+
+use chrono::{DateTime, TimeZone, Utc};
+use ring::{constant_time, digest};
+use sqlx::PgPool;
+use uuid::Uuid;
+use webgame_collection_api_macros::Error;
+
+use crate::config::CONFIG;
+
+use super::{
+    create_random_token, get_game_authorization_code_key, get_game_tokens_key,
+    get_invalid_token_key, jwt::JWT_KEYS, login::Claims, scope::Scope,
+};
+
+pub struct DelegatedToken {
+    pub access_token: String,
+    pub game_id: Uuid,
+    pub scopes: Vec<Scope>,
+    pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Error)]
+pub enum DelegationError {
+    #[error(message = "Database error")]
+    DbError(sqlx::Error),
+    #[error(message = "Redis error")]
+    RedisError(redis::RedisError),
+    #[error(message = "Token creation failed")]
+    TokenCreationFailed,
+    #[error(message = "At least one scope is required")]
+    ScopesNotProvided,
+    #[error(message = "The scope cannot be delegated to a game")]
+    ScopeNotDelegable,
+    #[error(message = "Game not found")]
+    GameNotFound,
+    #[error(message = "Invalid or expired authorization code")]
+    InvalidCode,
+    #[error(message = "The game is not authorized by the user")]
+    ConsentNotFound,
+    #[error(message = "Invalid code challenge")]
+    InvalidCodeChallenge,
+}
+
+// PKCE(S256): 코드를 요청한 게임만 verifier로 교환할 수 있음
+fn code_challenge(code_verifier: &str) -> String {
+    base64::encode_config(
+        digest::digest(&digest::SHA256, code_verifier.as_bytes()),
+        base64::URL_SAFE_NO_PAD,
+    )
+}
+
+// 유저가 게임에 범위를 위임하는 데 동의하면 짧은 수명의 일회용 코드를 발급
+pub async fn authorize_game(
+    pool: &PgPool,
+    redis_conn: &mut deadpool_redis::Connection,
+    user_id: &Uuid,
+    session_id: Option<Uuid>,
+    game_id: &Uuid,
+    scopes: Vec<Scope>,
+    code_challenge: String,
+) -> Result<String, DelegationError> {
+    if scopes.is_empty() {
+        return Err(DelegationError::ScopesNotProvided);
+    }
+    // SHA-256 해시를 base64url로 인코딩한 값은 항상 43자
+    if code_challenge.len() != 43 {
+        return Err(DelegationError::InvalidCodeChallenge);
+    }
+    if scopes.iter().any(|scope| !scope.is_delegable()) {
+        return Err(DelegationError::ScopeNotDelegable);
+    }
+
+    sqlx::query!(
+        r#"
+        SELECT id FROM public.game
+        WHERE id = $1
+        "#,
+        game_id,
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(DelegationError::DbError)?
+    .ok_or(DelegationError::GameNotFound)?;
+
+    let scope_names = scopes.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+
+    sqlx::query!(
+        r#"
+        INSERT INTO public.game_consent (user_id, game_id, scopes)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (user_id, game_id) DO UPDATE
+        SET scopes = EXCLUDED.scopes, granted_at = CURRENT_TIMESTAMP, revoked_at = NULL
+        "#,
+        user_id,
+        game_id,
+        &scope_names,
+    )
+    .execute(pool)
+    .await
+    .map_err(DelegationError::DbError)?;
+
+    let code = create_random_token().ok_or(DelegationError::TokenCreationFailed)?;
+    let key = get_game_authorization_code_key(&code);
+
+    let mut fields = vec![
+        ("user_id", user_id.to_string()),
+        ("game_id", game_id.to_string()),
+        ("scopes", scope_names.join(" ")),
+        ("code_challenge", code_challenge),
+    ];
+    if let Some(session_id) = session_id {
+        fields.push(("session_id", session_id.to_string()));
+    }
+
+    redis::pipe()
+        .cmd("HSET")
+        .arg(&key)
+        .arg(&fields)
+        .ignore()
+        .cmd("EXPIRE")
+        .arg(&key)
+        .arg(CONFIG.game_authorization_code_ttl)
+        .query_async::<_, ()>(redis_conn)
+        .await
+        .map_err(DelegationError::RedisError)?;
+
+    Ok(code)
+}
+
+pub async fn exchange_authorization_code(
+    pool: &PgPool,
+    redis_conn: &mut deadpool_redis::Connection,
+    code: &str,
+    code_verifier: &str,
+) -> Result<DelegatedToken, DelegationError> {
+    let key = get_game_authorization_code_key(code);
+
+    // 코드는 한 번만 교환할 수 있도록 읽는 즉시 삭제
+    let (fields,) = redis::pipe()
+        .atomic()
+        .cmd("HGETALL")
+        .arg(&key)
+        .cmd("DEL")
+        .arg(&key)
+        .ignore()
+        .query_async::<_, (HashMap<String, String>,)>(redis_conn)
+        .await
+        .map_err(DelegationError::RedisError)?;
+
+    // 코드가 유출되더라도 verifier 없이는 교환할 수 없음
+    let verified = fields.get("code_challenge").map_or(false, |challenge| {
+        constant_time::verify_slices_are_equal(
+            challenge.as_bytes(),
+            code_challenge(code_verifier).as_bytes(),
+        )
+        .is_ok()
+    });
+    if !verified {
+        return Err(DelegationError::InvalidCode);
+    }
+
+    let parse_uuid = |name: &str| fields.get(name).and_then(|v| Uuid::from_str(v).ok());
+    let user_id = parse_uuid("user_id").ok_or(DelegationError::InvalidCode)?;
+    let game_id = parse_uuid("game_id").ok_or(DelegationError::InvalidCode)?;
+    let session_id = parse_uuid("session_id");
+    let requested = fields
+        .get("scopes")
+        .map(|scopes| {
+            scopes
+                .split(' ')
+                .filter_map(|s| s.parse().ok())
+                .collect::<Vec<Scope>>()
+        })
+        .ok_or(DelegationError::InvalidCode)?;
+
+    // 코드 발급 이후 동의가 철회되었다면 교환할 수 없음
+    let consent = sqlx::query!(
+        r#"
+        SELECT c.scopes FROM public.game_consent c
+        JOIN public.user u ON u.id = c.user_id
+        WHERE c.user_id = $1 AND c.game_id = $2
+            AND c.revoked_at IS NULL AND u.deleted_at IS NULL
+        "#,
+        user_id,
+        game_id,
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(DelegationError::DbError)?
+    .ok_or(DelegationError::ConsentNotFound)?;
+
+    let scopes = requested
+        .into_iter()
+        .filter(|scope| scope.is_delegable())
+        .filter(|scope| consent.scopes.iter().any(|s| s == scope.as_str()))
+        .collect::<Vec<_>>();
+    if scopes.is_empty() {
+        return Err(DelegationError::ConsentNotFound);
+    }
+
+    // 동의한 세션이 로그아웃되면 위임된 토큰도 함께 무효화됨
+    let claims = Claims {
+        gid: Some(game_id.to_string()),
+        scp: Some(scopes.clone()),
+        ..Claims::new(&user_id, session_id.as_ref()).ok_or(DelegationError::TokenCreationFailed)?
+    };
+    let expires_at = Utc.timestamp(claims.exp as i64, 0);
+    let access_token = JWT_KEYS
+        .encode(&claims)
+        .ok_or(DelegationError::TokenCreationFailed)?;
+
+    // 동의를 철회할 때 이미 발급된 토큰도 무효화할 수 있도록 기록
+    let tokens_key = get_game_tokens_key(&user_id, &game_id);
+    redis::pipe()
+        .cmd("SADD")
+        .arg(&tokens_key)
+        .arg(format!("{}:{}", claims.jti, claims.exp))
+        .ignore()
+        .cmd("EXPIREAT")
+        .arg(&tokens_key)
+        .arg(claims.exp)
+        .ignore()
+        .query_async::<_, ()>(redis_conn)
+        .await
+        .map_err(DelegationError::RedisError)?;
+
+    Ok(DelegatedToken {
+        access_token,
+        game_id,
+        scopes,
+        expires_at,
+    })
+}
+
+pub async fn revoke_game_authorization(
+    pool: &PgPool,
+    redis_conn: &mut deadpool_redis::Connection,
+    user_id: &Uuid,
+    game_id: &Uuid,
+) -> Result<(), DelegationError> {
+    sqlx::query!(
+        r#"
+        UPDATE public.game_consent
+        SET revoked_at = CURRENT_TIMESTAMP
+        WHERE user_id = $1 AND game_id = $2 AND revoked_at IS NULL
+        RETURNING user_id
+        "#,
+        user_id,
+        game_id,
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(DelegationError::DbError)?
+    .ok_or(DelegationError::ConsentNotFound)?;
+
+    let tokens_key = get_game_tokens_key(user_id, game_id);
+    let (tokens,) = redis::pipe()
+        .atomic()
+        .cmd("SMEMBERS")
+        .arg(&tokens_key)
+        .cmd("DEL")
+        .arg(&tokens_key)
+        .ignore()
+        .query_async::<_, (Vec<String>,)>(redis_conn)
+        .await
+        .map_err(DelegationError::RedisError)?;
+
+    // AuthInfo::invalidate와 같이 만료 시각까지 폐기 목록에 등록
+    let mut pipe = redis::pipe();
+    for token in &tokens {
+        if let Some((token_id, token_exp)) = token.split_once(':') {
+            let key = get_invalid_token_key(token_id);
+            pipe.cmd("SET")
+                .arg(&key)
+                .arg(token_exp)
+                .ignore()
+                .cmd("EXPIREAT")
+                .arg(&key)
+                .arg(token_exp)
+                .ignore();
+        }
+    }
+
+    pipe.query_async::<_, ()>(redis_conn)
+        .await
+        .map_err(DelegationError::RedisError)
+}
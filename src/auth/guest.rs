@@ -0,0 +1,261 @@
+use chrono::{Duration, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+use webgame_collection_api_macros::Error;
+
+use crate::{
+    config::CONFIG,
+    schema::types::{
+        node::{IdData, NodeIdent},
+        scalars::DateTimeScalar,
+        user::{User, UserRegisterInput},
+    },
+};
+
+use super::{
+    account::purge_user_data, get_guest_creation_key, password_data::PasswordData, AuthMethodType,
+};
+
+#[derive(Error)]
+pub enum GuestError {
+    #[error(message = "Database error")]
+    DbError(sqlx::Error),
+    #[error(message = "Redis error")]
+    RedisError(redis::RedisError),
+    #[error(message = "Too many guest accounts created, try again in {0} seconds")]
+    RateLimited(i64),
+    #[error(message = "The user is not a guest")]
+    NotGuest,
+    #[error(message = "User already exists")]
+    UserAlreadyExists,
+    #[error(message = "Auth method already registered")]
+    MethodAlreadyRegistered,
+    #[error(message = "Password data not present")]
+    PasswordDataNotPresent,
+    #[error(message = "Password data invalid")]
+    PasswordDataInvalid,
+}
+
+// IP마다 일정 시간 동안 만들 수 있는 게스트 계정 수를 제한
+pub async fn check_guest_creation_limit(
+    ip: Option<&str>,
+    redis_conn: &mut deadpool_redis::Connection,
+) -> Result<(), GuestError> {
+    let key = get_guest_creation_key(ip.unwrap_or("unknown"));
+
+    let (count, ttl) = redis::pipe()
+        .atomic()
+        .cmd("SET")
+        .arg(&key)
+        .arg(0)
+        .arg("EX")
+        .arg(CONFIG.guest_creation_window)
+        .arg("NX")
+        .ignore()
+        .cmd("INCR")
+        .arg(&key)
+        .cmd("TTL")
+        .arg(&key)
+        .query_async::<_, (i64, i64)>(redis_conn)
+        .await
+        .map_err(GuestError::RedisError)?;
+
+    match count > CONFIG.guest_creation_limit {
+        true => Err(GuestError::RateLimited(ttl.max(0))),
+        false => Ok(()),
+    }
+}
+
+pub async fn create_guest(pool: &PgPool) -> Result<Uuid, GuestError> {
+    let id = Uuid::new_v4();
+    let suffix = id.to_simple().to_string();
+
+    // 닉네임과 이메일은 유니크해야 하므로 ID로부터 생성
+    sqlx::query!(
+        r#"
+        INSERT INTO public.user (id, nickname, email, is_guest, registered_at, last_active_at)
+        VALUES ($1, $2, $3, TRUE, CURRENT_TIMESTAMP, CURRENT_TIMESTAMP)
+        "#,
+        id,
+        format!("Guest-{}", &suffix[..12]),
+        format!("{}@guest.invalid", suffix),
+    )
+    .execute(pool)
+    .await
+    .map_err(GuestError::DbError)?;
+
+    Ok(id)
+}
+
+pub async fn upgrade_guest(
+    pool: &PgPool,
+    user_id: &Uuid,
+    input: UserRegisterInput,
+    auth_type: AuthMethodType,
+    identifier: String,
+    password_data: Option<PasswordData>,
+) -> Result<User, GuestError> {
+    if auth_type == AuthMethodType::Email && password_data.is_none() {
+        return Err(GuestError::PasswordDataNotPresent);
+    }
+
+    let data = match password_data {
+        Some(data) => match serde_json::to_value(data) {
+            Ok(data) => Ok(Some(data)),
+            Err(_) => Err(GuestError::PasswordDataInvalid),
+        },
+        None => Ok(None),
+    }?;
+
+    let mut tx = pool.begin().await.map_err(GuestError::DbError)?;
+
+    sqlx::query!(
+        r#"
+        SELECT id FROM public.user
+        WHERE id = $1 AND is_guest AND deleted_at IS NULL
+        FOR UPDATE
+        "#,
+        user_id,
+    )
+    .fetch_optional(&mut tx)
+    .await
+    .map_err(GuestError::DbError)?
+    .ok_or(GuestError::NotGuest)?;
+
+    let existing_user = sqlx::query!(
+        r#"
+        SELECT id FROM public.user
+        WHERE id <> $1 AND (nickname = $2 OR email = $3)
+        "#,
+        user_id,
+        input.nickname,
+        input.email,
+    )
+    .fetch_optional(&mut tx)
+    .await
+    .map_err(GuestError::DbError)?;
+
+    if existing_user.is_some() {
+        return Err(GuestError::UserAlreadyExists);
+    }
+
+    let existing_method = sqlx::query!(
+        r#"
+        SELECT user_id FROM public.user_auth_method
+        WHERE type = $1 AND identifier = $2
+        "#,
+        &auth_type as &AuthMethodType,
+        identifier,
+    )
+    .fetch_optional(&mut tx)
+    .await
+    .map_err(GuestError::DbError)?;
+
+    if existing_method.is_some() {
+        return Err(GuestError::MethodAlreadyRegistered);
+    }
+
+    // 유저 ID는 그대로 유지되므로 게스트로 쌓은 데이터도 유지됨
+    let user = sqlx::query!(
+        r#"
+        UPDATE public.user
+        SET nickname = $2,
+            email = $3,
+            is_guest = FALSE,
+            email_verified_at = NULL,
+            last_active_at = CURRENT_TIMESTAMP
+        WHERE id = $1
+        RETURNING *
+        "#,
+        user_id,
+        input.nickname,
+        input.email,
+    )
+    .fetch_one(&mut tx)
+    .await
+    .map_err(GuestError::DbError)?;
+
+    sqlx::query!(
+        r#"
+        INSERT INTO public.user_auth_method (user_id, type, identifier, extra_info)
+        VALUES ($1, $2, $3, $4)
+        "#,
+        user_id,
+        auth_type as AuthMethodType,
+        identifier,
+        data
+    )
+    .execute(&mut tx)
+    .await
+    .map_err(GuestError::DbError)?;
+
+    tx.commit().await.map_err(GuestError::DbError)?;
+
+    Ok(User {
+        id: IdData {
+            ty: NodeIdent::User,
+            uuid: user.id,
+        }
+        .to_id_scalar(),
+        uuid: user.id,
+        nickname: user.nickname,
+        email: user.email,
+        email_verified: user.email_verified_at.is_some(),
+        avatar_url: user.avatar_url,
+        bio: user.bio,
+        is_guest: user.is_guest,
+        registered_at: DateTimeScalar(user.registered_at),
+        deleted_at: user.deleted_at.map(DateTimeScalar),
+    })
+}
+
+// 삭제된 유저라면 false를 반환
+pub async fn touch_last_active(pool: &PgPool, user_id: &Uuid) -> Result<bool, sqlx::Error> {
+    sqlx::query!(
+        r#"
+        UPDATE public.user
+        SET last_active_at = CURRENT_TIMESTAMP
+        WHERE id = $1 AND deleted_at IS NULL
+        RETURNING id
+        "#,
+        user_id,
+    )
+    .fetch_optional(pool)
+    .await
+    .map(|user| user.is_some())
+}
+
+// 게스트는 로그인 수단이 없어 복구할 수 없으므로 유예 기간 없이 바로 정리
+pub async fn purge_inactive_guests(pool: &PgPool) -> Result<Vec<Uuid>, sqlx::Error> {
+    let cutoff = Utc::now() - Duration::seconds(CONFIG.guest_max_inactive_age);
+
+    let mut tx = pool.begin().await?;
+
+    let user_ids = sqlx::query!(
+        r#"
+        UPDATE public.user
+        SET nickname = 'deleted-' || id::text,
+            email = id::text || '@deleted.invalid',
+            avatar_url = NULL,
+            bio = NULL,
+            deleted_at = CURRENT_TIMESTAMP,
+            purged_at = CURRENT_TIMESTAMP
+        WHERE is_guest
+            AND deleted_at IS NULL
+            AND COALESCE(last_active_at, registered_at) < $1
+        RETURNING id
+        "#,
+        cutoff,
+    )
+    .fetch_all(&mut tx)
+    .await?
+    .into_iter()
+    .map(|user| user.id)
+    .collect::<Vec<_>>();
+
+    purge_user_data(&mut tx, &user_ids).await?;
+
+    tx.commit().await?;
+
+    Ok(user_ids)
+}
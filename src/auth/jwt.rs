@@ -0,0 +1,152 @@
+use std::{collections::HashMap, fs};
+
+use jsonwebtoken::{
+    jwk::{AlgorithmParameters, EllipticCurve, Jwk, JwkSet},
+    Algorithm, DecodingKey, EncodingKey, Header, Validation,
+};
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::config::CONFIG;
+
+pub struct JwtKeys {
+    signing_header: Header,
+    signing_key: EncodingKey,
+    verification_keys: HashMap<String, (Algorithm, DecodingKey)>,
+    legacy_key: Option<DecodingKey>,
+    pub jwks: JwkSet,
+}
+
+lazy_static::lazy_static! {
+    pub static ref JWT_KEYS: JwtKeys = JwtKeys::load();
+}
+
+impl JwtKeys {
+    fn load() -> JwtKeys {
+        let mut jwks = match &CONFIG.jwks_path {
+            Some(path) => {
+                let content = fs::read(path).expect("Failed to read the JWKS file");
+                serde_json::from_slice::<JwkSet>(&content).expect("Failed to parse the JWKS file")
+            }
+            None => JwkSet { keys: vec![] },
+        };
+        // 공개되는 키 목록에 대칭 키가 포함되지 않도록 제외
+        jwks.keys
+            .retain(|jwk| !matches!(jwk.algorithm, AlgorithmParameters::OctetKey(_)));
+
+        let verification_keys: HashMap<_, _> = jwks
+            .keys
+            .iter()
+            .filter_map(|jwk| {
+                let kid = jwk.common.key_id.clone()?;
+                let algorithm = match jwk_algorithm(jwk) {
+                    Some(algorithm) => algorithm,
+                    None => {
+                        log::warn!("Skipping the JWK {} with an ambiguous algorithm", kid);
+                        return None;
+                    }
+                };
+                let key = DecodingKey::from_jwk(jwk).ok()?;
+
+                Some((kid, (algorithm, key)))
+            })
+            .collect();
+
+        let (signing_header, signing_key) = match &CONFIG.jwt_signing_key {
+            Some(config) => {
+                let pem = fs::read(&config.private_key_path)
+                    .expect("Failed to read the JWT signing key file");
+                let key = match config.algorithm {
+                    Algorithm::EdDSA => EncodingKey::from_ed_pem(&pem),
+                    Algorithm::ES256 | Algorithm::ES384 => EncodingKey::from_ec_pem(&pem),
+                    _ => EncodingKey::from_rsa_pem(&pem),
+                }
+                .expect("Failed to parse the JWT signing key");
+
+                assert!(
+                    verification_keys.contains_key(&config.kid),
+                    "JWT signing key must be listed in the JWKS file"
+                );
+
+                let mut header = Header::new(config.algorithm);
+                header.kid = Some(config.kid.clone());
+
+                (header, key)
+            }
+            // 비대칭 키가 설정되지 않은 경우 기존과 같이 공유 secret으로 서명
+            None => (
+                Header::new(Algorithm::HS512),
+                EncodingKey::from_secret(CONFIG.jwt_secret.as_slice()),
+            ),
+        };
+
+        // 비대칭 키로 서명하게 되면 기본적으로 kid가 없는 기존 토큰을 더 이상 받지 않음
+        let legacy_fallback = CONFIG
+            .jwt_legacy_fallback
+            .unwrap_or_else(|| CONFIG.jwt_signing_key.is_none());
+        assert!(
+            legacy_fallback || CONFIG.jwt_signing_key.is_some(),
+            "JWT legacy fallback can't be disabled without a JWT signing key"
+        );
+
+        JwtKeys {
+            signing_header,
+            signing_key,
+            verification_keys,
+            legacy_key: match legacy_fallback {
+                true => Some(DecodingKey::from_secret(CONFIG.jwt_secret.as_slice())),
+                false => None,
+            },
+            jwks,
+        }
+    }
+
+    pub fn encode<T: Serialize>(&self, claims: &T) -> Option<String> {
+        jsonwebtoken::encode(&self.signing_header, claims, &self.signing_key).ok()
+    }
+
+    pub fn decode<T: DeserializeOwned>(
+        &self,
+        token: &str,
+    ) -> Result<T, jsonwebtoken::errors::Error> {
+        let header = jsonwebtoken::decode_header(token)?;
+
+        let (algorithm, key) = match &header.kid {
+            Some(kid) => self
+                .verification_keys
+                .get(kid)
+                .map(|(algorithm, key)| (*algorithm, key))
+                .ok_or(jsonwebtoken::errors::ErrorKind::InvalidSignature)?,
+            // kid가 없는 토큰은 공유 secret으로 서명된 기존 토큰으로 간주
+            None => self
+                .legacy_key
+                .as_ref()
+                .map(|key| (Algorithm::HS512, key))
+                .ok_or(jsonwebtoken::errors::ErrorKind::InvalidSignature)?,
+        };
+
+        let mut validation = Validation::new(algorithm);
+        validation.validate_nbf = true;
+        validation.set_issuer(&[&CONFIG.jwt_issuer]);
+        validation.set_audience(&[&CONFIG.jwt_audience]);
+
+        jsonwebtoken::decode::<T>(token, key, &validation).map(|data| data.claims)
+    }
+}
+
+// alg가 명시되지 않은 키는 키 종류와 곡선으로 알고리즘을 결정
+fn jwk_algorithm(jwk: &Jwk) -> Option<Algorithm> {
+    if let Some(algorithm) = jwk.common.algorithm {
+        return Some(algorithm);
+    }
+
+    match &jwk.algorithm {
+        AlgorithmParameters::OctetKeyPair(_) => Some(Algorithm::EdDSA),
+        AlgorithmParameters::EllipticCurve(params) => match params.curve {
+            EllipticCurve::P256 => Some(Algorithm::ES256),
+            EllipticCurve::P384 => Some(Algorithm::ES384),
+            _ => None,
+        },
+        AlgorithmParameters::RSA(_) => Some(Algorithm::RS256),
+        AlgorithmParameters::OctetKey(_) => None,
+    }
+}
@@ -0,0 +1,289 @@
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use uuid::Uuid;
+use webgame_collection_api_macros::Error;
+
+use crate::{
+    config::CONFIG,
+    mail::{Mail, MailError, Mailer},
+    schema::types::user::AuthMethod,
+};
+
+use super::{
+    create_random_token, get_email_link_token_key, password_data::PasswordData, AuthMethodType,
+};
+
+#[derive(Error)]
+pub enum LinkError {
+    #[error(message = "Database error")]
+    DbError(sqlx::Error),
+    #[error(message = "Auth method already registered")]
+    MethodAlreadyRegistered,
+    #[error(message = "No matching auth method found")]
+    MethodNotFound,
+    #[error(message = "Cannot unlink the last remaining auth method")]
+    LastMethod,
+    #[error(message = "Password data not present")]
+    PasswordDataNotPresent,
+    #[error(message = "Password data invalid")]
+    PasswordDataInvalid,
+    #[error(message = "Guest accounts must be upgraded instead")]
+    GuestAccount,
+    #[error(message = "Redis error")]
+    RedisError(redis::RedisError),
+    #[error(message = "Mail sending failed")]
+    MailError(MailError),
+    #[error(message = "Token creation failed")]
+    TokenCreationFailed,
+    #[error(message = "Invalid or expired link token")]
+    InvalidToken,
+}
+
+// 이메일 연결은 인증 코드를 확인할 때까지 Redis에 보관
+#[derive(Serialize, Deserialize)]
+struct PendingEmailLink {
+    user_id: Uuid,
+    email: String,
+    password_data: PasswordData,
+}
+
+pub async fn get_auth_methods(pool: &PgPool, user_id: &Uuid) -> Result<Vec<AuthMethod>, LinkError> {
+    let methods = sqlx::query!(
+        r#"
+        SELECT
+            type AS "ty: AuthMethodType",
+            identifier
+        FROM public.user_auth_method
+        WHERE user_id = $1
+        "#,
+        user_id,
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(LinkError::DbError)?;
+
+    Ok(methods
+        .into_iter()
+        .map(|method| AuthMethod {
+            ty: method.ty,
+            identifier: method.identifier,
+        })
+        .collect())
+}
+
+async fn check_linkable(
+    pool: &PgPool,
+    user_id: &Uuid,
+    auth_type: &AuthMethodType,
+    identifier: &str,
+) -> Result<(), LinkError> {
+    let is_guest = sqlx::query!(
+        r#"
+        SELECT is_guest FROM public.user
+        WHERE id = $1
+        "#,
+        user_id,
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(LinkError::DbError)?
+    .map_or(false, |user| user.is_guest);
+
+    if is_guest {
+        return Err(LinkError::GuestAccount);
+    }
+
+    let existing_method = sqlx::query!(
+        r#"
+        SELECT user_id FROM public.user_auth_method
+        WHERE type = $1 AND identifier = $2
+        "#,
+        auth_type as &AuthMethodType,
+        identifier,
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(LinkError::DbError)?;
+
+    if existing_method.is_some() {
+        return Err(LinkError::MethodAlreadyRegistered);
+    }
+
+    Ok(())
+}
+
+pub async fn link_auth_method(
+    pool: &PgPool,
+    user_id: &Uuid,
+    auth_type: AuthMethodType,
+    identifier: String,
+    password_data: Option<PasswordData>,
+) -> Result<(), LinkError> {
+    if auth_type == AuthMethodType::Email && password_data.is_none() {
+        return Err(LinkError::PasswordDataNotPresent);
+    }
+
+    let data = match password_data {
+        Some(data) => match serde_json::to_value(data) {
+            Ok(data) => Ok(Some(data)),
+            Err(_) => Err(LinkError::PasswordDataInvalid),
+        },
+        None => Ok(None),
+    }?;
+
+    check_linkable(pool, user_id, &auth_type, &identifier).await?;
+
+    sqlx::query!(
+        r#"
+        INSERT INTO public.user_auth_method (user_id, type, identifier, extra_info)
+        VALUES ($1, $2, $3, $4)
+        "#,
+        user_id,
+        auth_type as AuthMethodType,
+        identifier,
+        data
+    )
+    .execute(pool)
+    .await
+    .map_err(LinkError::DbError)?;
+
+    Ok(())
+}
+
+pub async fn request_email_link(
+    pool: &PgPool,
+    mailer: &dyn Mailer,
+    redis_conn: &mut deadpool_redis::Connection,
+    user_id: &Uuid,
+    email: String,
+    password_data: PasswordData,
+) -> Result<(), LinkError> {
+    check_linkable(pool, user_id, &AuthMethodType::Email, &email).await?;
+
+    let token = create_random_token().ok_or(LinkError::TokenCreationFailed)?;
+    let key = get_email_link_token_key(&token);
+    let value = serde_json::to_string(&PendingEmailLink {
+        user_id: *user_id,
+        email: email.clone(),
+        password_data,
+    })
+    .map_err(|_| LinkError::PasswordDataInvalid)?;
+
+    redis::pipe()
+        .cmd("SET")
+        .arg(&[&key, &value])
+        .ignore()
+        .cmd("EXPIRE")
+        .arg(&key)
+        .arg(CONFIG.email_verification_ttl)
+        .query_async::<_, ()>(redis_conn)
+        .await
+        .map_err(LinkError::RedisError)?;
+
+    mailer
+        .send(Mail {
+            to: email,
+            subject: "Confirm your email address".to_owned(),
+            body: format!(
+                "Use the following token to add this email address to your account: {}",
+                token
+            ),
+        })
+        .await
+        .map_err(LinkError::MailError)
+}
+
+pub async fn confirm_email_link(
+    pool: &PgPool,
+    redis_conn: &mut deadpool_redis::Connection,
+    user_id: &Uuid,
+    token: &str,
+) -> Result<(), LinkError> {
+    let key = get_email_link_token_key(token);
+
+    let (value,): (Option<String>,) = redis::pipe()
+        .atomic()
+        .cmd("GET")
+        .arg(&key)
+        .cmd("DEL")
+        .arg(&key)
+        .ignore()
+        .query_async(redis_conn)
+        .await
+        .map_err(LinkError::RedisError)?;
+
+    // 다른 유저가 요청한 토큰은 사용할 수 없음
+    let pending = value
+        .and_then(|value| serde_json::from_str::<PendingEmailLink>(&value).ok())
+        .filter(|pending| &pending.user_id == user_id)
+        .ok_or(LinkError::InvalidToken)?;
+
+    link_auth_method(
+        pool,
+        user_id,
+        AuthMethodType::Email,
+        pending.email,
+        Some(pending.password_data),
+    )
+    .await
+}
+
+pub async fn unlink_auth_method(
+    pool: &PgPool,
+    user_id: &Uuid,
+    auth_type: AuthMethodType,
+    identifier: String,
+) -> Result<(), LinkError> {
+    let mut tx = pool.begin().await.map_err(LinkError::DbError)?;
+
+    // 동시에 여러 방식을 해제하는 경우 마지막 방식까지 지워지지 않도록
+    // 유저 row를 잠근 상태에서 개수를 확인
+    sqlx::query!(
+        r#"
+        SELECT id FROM public.user
+        WHERE id = $1
+        FOR UPDATE
+        "#,
+        user_id,
+    )
+    .fetch_one(&mut tx)
+    .await
+    .map_err(LinkError::DbError)?;
+
+    let count = sqlx::query!(
+        r#"
+        SELECT COUNT(*) AS "count!" FROM public.user_auth_method
+        WHERE user_id = $1
+        "#,
+        user_id,
+    )
+    .fetch_one(&mut tx)
+    .await
+    .map_err(LinkError::DbError)?
+    .count;
+
+    if count <= 1 {
+        return Err(LinkError::LastMethod);
+    }
+
+    let result = sqlx::query!(
+        r#"
+        DELETE FROM public.user_auth_method
+        WHERE user_id = $1 AND type = $2 AND identifier = $3
+        "#,
+        user_id,
+        auth_type as AuthMethodType,
+        identifier,
+    )
+    .execute(&mut tx)
+    .await
+    .map_err(LinkError::DbError)?;
+
+    if result.rows_affected() == 0 {
+        return Err(LinkError::MethodNotFound);
+    }
+
+    tx.commit().await.map_err(LinkError::DbError)?;
+
+    Ok(())
+}
@@ -1,24 +1,42 @@
 use chrono::{Duration, Utc};
-use jsonwebtoken::{Algorithm, EncodingKey, Header};
 use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
 use uuid::Uuid;
 use webgame_collection_api_macros::Error;
 
-use super::{password_data::PasswordData, AuthMethodType};
+use crate::{config::CONFIG, error::Error};
+
+use super::{
+    account::is_restorable,
+    client_info::ClientInfo,
+    jwt::JWT_KEYS,
+    password_data::PasswordData,
+    role::Role,
+    scope::Scope,
+    throttle::{register_login_attempt, reset_login_failures},
+    AuthMethodType,
+};
 
 #[derive(Error)]
 pub enum LoginError {
     #[error(message = "Database error")]
     DbError(sqlx::Error),
+    #[error(message = "Redis error")]
+    RedisError(redis::RedisError),
     #[error(message = "Password not provided while required for the requested method type")]
     PasswordNotProvided,
     #[error(message = "No matching auth method found")]
     MethodNotFound,
     #[error(message = "Invalid auth method data detected")]
     InvalidMethodData,
-    #[error(message = "Wrong password")]
-    WrongPassword,
+    #[error(message = "Invalid credentials")]
+    InvalidCredentials,
+    #[error(message = "Too many failed login attempts, try again in {0} seconds")]
+    LockedOut(i64),
+    #[error(message = "Email address not verified")]
+    EmailNotVerified,
+    #[error(message = "Account has been deleted")]
+    AccountDeleted,
 }
 
 pub async fn verify_auth_method(
@@ -30,51 +48,193 @@ pub async fn verify_auth_method(
     let method = sqlx::query!(
         r#"
         SELECT
-            user_id,
-            extra_info
-        FROM public.user_auth_method
-        WHERE type = $1 AND identifier = $2
+            m.user_id,
+            m.extra_info,
+            u.email_verified_at,
+            u.deleted_at
+        FROM public.user_auth_method m
+        JOIN public.user u ON u.id = m.user_id
+        WHERE m.type = $1 AND m.identifier = $2
         "#,
         &auth_type as &AuthMethodType,
         identifier,
     )
     .fetch_optional(pool)
     .await
-    .map_err(LoginError::DbError)?
-    .ok_or(LoginError::MethodNotFound)?;
+    .map_err(LoginError::DbError)?;
+
+    let method = match method {
+        Some(method) => method,
+        None if auth_type == AuthMethodType::Email => {
+            // 존재하지 않는 이메일인지 응답 시간으로 구분할 수 없도록 해시를 한 번 계산
+            if let Some(password) = &password {
+                let _ =
+                    PasswordData::new(password, CONFIG.pbkdf2_salt_size, CONFIG.password_params());
+            }
+            return Err(LoginError::InvalidCredentials);
+        }
+        None => return Err(LoginError::MethodNotFound),
+    };
 
-    let password_data: Option<PasswordData> = match method.extra_info {
+    let password_data: Option<PasswordData> = match &method.extra_info {
         Some(data) => {
-            Some(serde_json::from_value(data).map_err(|_| LoginError::InvalidMethodData)?)
+            Some(serde_json::from_value(data.clone()).map_err(|_| LoginError::InvalidMethodData)?)
         }
         None => None,
     };
 
-    if auth_type == AuthMethodType::Email
-        && !password_data
-            .ok_or(LoginError::InvalidMethodData)?
-            .verify(&password.ok_or(LoginError::PasswordNotProvided)?)
+    if auth_type == AuthMethodType::Email {
+        let password_data = password_data.ok_or(LoginError::InvalidMethodData)?;
+        let password = password.ok_or(LoginError::PasswordNotProvided)?;
+
+        if !password_data.verify(&password) {
+            return Err(LoginError::InvalidCredentials);
+        }
+        if CONFIG.require_email_verification && method.email_verified_at.is_none() {
+            return Err(LoginError::EmailNotVerified);
+        }
+
+        // 로그인 시점에만 평문 비밀번호를 알 수 있으므로,
+        // 현재 설정과 다른 방식으로 저장된 비밀번호는 이때 다시 해시해서 저장
+        let params = CONFIG.password_params();
+        if password_data.needs_rehash(params) {
+            if let Err(e) = rehash_password(pool, &identifier, &password, &method.extra_info).await
+            {
+                log::warn!("Failed to rehash the password of {}: {}", identifier, e);
+            }
+        }
+    }
+
+    // 유예 기간 내에 삭제된 계정은 로그인을 마치면 복구되므로 통과시킴
+    match method.deleted_at {
+        Some(deleted_at) if !is_restorable(&deleted_at) => Err(LoginError::AccountDeleted),
+        _ => Ok(method.user_id),
+    }
+}
+
+pub async fn login_email(
+    pool: &PgPool,
+    redis_conn: &mut deadpool_redis::Connection,
+    client_info: &ClientInfo,
+    email: String,
+    password: String,
+) -> Result<Uuid, LoginError> {
+    let ip = client_info.ip.as_deref();
+
+    // 시도는 검증 전에 실패로 기록되며, 성공하면 되돌림
+    if let Some(remaining) = register_login_attempt(&email, ip, redis_conn)
+        .await
+        .map_err(LoginError::RedisError)?
     {
-        Err(LoginError::WrongPassword)
-    } else {
-        Ok(method.user_id)
+        return Err(LoginError::LockedOut(remaining));
     }
+
+    let result =
+        verify_auth_method(pool, AuthMethodType::Email, email.clone(), Some(password)).await;
+
+    // 인증 전이거나 삭제된 계정이라는 오류는 비밀번호를 확인한 뒤에만 반환되므로 실패로 세지 않음
+    if matches!(
+        result,
+        Ok(_) | Err(LoginError::EmailNotVerified) | Err(LoginError::AccountDeleted)
+    ) {
+        reset_login_failures(&email, ip, redis_conn)
+            .await
+            .map_err(LoginError::RedisError)?;
+    }
+
+    result
+}
+
+// 로그인 실패를 기록할 때 계정이 존재한다면 해당 유저와 연결하기 위해 사용
+pub async fn find_email_user_id(pool: &PgPool, email: &str) -> Result<Option<Uuid>, sqlx::Error> {
+    sqlx::query!(
+        r#"
+        SELECT user_id FROM public.user_auth_method
+        WHERE type = $1 AND identifier = $2
+        "#,
+        AuthMethodType::Email as AuthMethodType,
+        email,
+    )
+    .fetch_optional(pool)
+    .await
+    .map(|method| method.map(|method| method.user_id))
+}
+
+// 그 사이 비밀번호가 변경되었다면 이전 비밀번호의 해시로 덮어쓰지 않도록 검증한 값과 같을 때만 갱신
+async fn rehash_password(
+    pool: &PgPool,
+    identifier: &str,
+    password: &str,
+    verified_extra_info: &Option<serde_json::Value>,
+) -> Result<(), anyhow::Error> {
+    let password_data =
+        PasswordData::new(password, CONFIG.pbkdf2_salt_size, CONFIG.password_params())
+            .map_err(|e| anyhow::anyhow!(e.message()))?;
+
+    sqlx::query!(
+        r#"
+        UPDATE public.user_auth_method
+        SET extra_info = $1
+        WHERE type = $2 AND identifier = $3 AND extra_info::jsonb = $4::jsonb
+        "#,
+        serde_json::to_value(password_data)?,
+        AuthMethodType::Email as AuthMethodType,
+        identifier,
+        verified_extra_info.as_ref(),
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Claims {
     pub sub: String,
     pub exp: usize,
+    pub iat: usize,
+    pub nbf: usize,
+    pub iss: String,
+    pub aud: String,
+    pub jti: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sid: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub roles: Vec<Role>,
+    // 게임에 위임된 토큰인 경우에만 존재
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gid: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scp: Option<Vec<Scope>>,
+}
+
+impl Claims {
+    pub fn new(sub: &Uuid, session_id: Option<&Uuid>) -> Option<Claims> {
+        let now = Utc::now();
+
+        Some(Claims {
+            sub: sub.to_string(),
+            exp: now
+                .checked_add_signed(Duration::seconds(CONFIG.access_token_lifetime))?
+                .timestamp() as usize,
+            iat: now.timestamp() as usize,
+            nbf: now.timestamp() as usize,
+            iss: CONFIG.jwt_issuer.clone(),
+            aud: CONFIG.jwt_audience.clone(),
+            jti: Uuid::new_v4().to_string(),
+            sid: session_id.map(|sid| sid.to_string()),
+            roles: vec![],
+            gid: None,
+            scp: None,
+        })
+    }
 }
 
-pub fn create_access_token(uuid: &Uuid, secret: &[u8]) -> Option<String> {
-    let header = Header::new(Algorithm::HS512);
+pub fn create_access_token(uuid: &Uuid, session_id: &Uuid, roles: Vec<Role>) -> Option<String> {
     let claims = Claims {
-        sub: uuid.to_string(),
-        exp: Utc::now()
-            .checked_add_signed(Duration::hours(1))?
-            .timestamp() as usize,
+        roles,
+        ..Claims::new(uuid, Some(session_id))?
     };
 
-    jsonwebtoken::encode(&header, &claims, &EncodingKey::from_secret(secret)).ok()
+    JWT_KEYS.encode(&claims)
 }
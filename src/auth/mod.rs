@@ -1,10 +1,34 @@
+pub mod account;
+pub mod api_key;
+pub mod audit;
 pub mod auth_info;
+pub mod client_info;
+pub mod connection_auth;
+pub mod delegation;
+pub mod guest;
+pub mod jwt;
+pub mod link;
 pub mod login;
+pub mod oauth;
+pub mod password;
 pub mod password_data;
+pub mod profile;
 pub mod refresh;
 pub mod register;
+pub mod role;
+pub mod scope;
+pub mod session;
+pub mod throttle;
+pub mod two_factor;
+pub mod verification;
 
-#[derive(sqlx::Type, PartialEq)]
+use async_graphql::Enum;
+use ring::rand::{SecureRandom, SystemRandom};
+use uuid::Uuid;
+
+use crate::schema::types::user::OAuthProvider;
+
+#[derive(sqlx::Type, Enum, Copy, Clone, Eq, PartialEq)]
 #[sqlx(type_name = "auth_method_type", rename_all = "lowercase")]
 pub enum AuthMethodType {
     Email,
@@ -13,12 +37,56 @@ pub enum AuthMethodType {
     Facebook,
 }
 
+impl AuthMethodType {
+    pub fn oauth_provider(&self) -> Option<OAuthProvider> {
+        match self {
+            AuthMethodType::Email => None,
+            AuthMethodType::Kakao => Some(OAuthProvider::Kakao),
+            AuthMethodType::Google => Some(OAuthProvider::Google),
+            AuthMethodType::Facebook => Some(OAuthProvider::Facebook),
+        }
+    }
+}
+
+impl From<OAuthProvider> for AuthMethodType {
+    fn from(provider: OAuthProvider) -> Self {
+        match provider {
+            OAuthProvider::Kakao => AuthMethodType::Kakao,
+            OAuthProvider::Google => AuthMethodType::Google,
+            OAuthProvider::Facebook => AuthMethodType::Facebook,
+        }
+    }
+}
+
 static INV_AUTH_TOKEN_REDIS_KEY: &str = "auth/invalidated_auth_token:";
 static REF_TOKEN_REDIS_KEY: &str = "auth/refresh_token:";
+static SESSION_REDIS_KEY: &str = "auth/session:";
+static USER_SESSIONS_REDIS_KEY: &str = "auth/user_sessions:";
+static EMAIL_VERIFICATION_TOKEN_REDIS_KEY: &str = "auth/email_verification_token:";
+static PASSWORD_RESET_TOKEN_REDIS_KEY: &str = "auth/password_reset_token:";
+static EMAIL_LINK_TOKEN_REDIS_KEY: &str = "auth/email_link_token:";
+static LOGIN_FAILURES_REDIS_KEY: &str = "auth/login_failures:";
+static LOGIN_LOCKOUT_REDIS_KEY: &str = "auth/login_lockout:";
+static TWO_FACTOR_CHALLENGE_REDIS_KEY: &str = "auth/two_factor_challenge:";
+static TWO_FACTOR_FAILURES_REDIS_KEY: &str = "auth/two_factor_failures:";
+static GAME_AUTHORIZATION_CODE_REDIS_KEY: &str = "auth/game_authorization_code:";
+static GAME_TOKENS_REDIS_KEY: &str = "auth/game_tokens:";
+static GUEST_CREATION_REDIS_KEY: &str = "auth/guest_creation:";
+static VERIFICATION_RESEND_REDIS_KEY: &str = "auth/verification_resend:";
+
+const RANDOM_TOKEN_SIZE: usize = 32;
 
-pub fn get_invalid_token_key(token: &str) -> String {
+pub fn create_random_token() -> Option<String> {
+    let mut buf = [0; RANDOM_TOKEN_SIZE];
+    let rng = SystemRandom::new();
+    rng.fill(&mut buf).ok()?;
+
+    Some(base64::encode_config(&buf, base64::URL_SAFE_NO_PAD))
+}
+
+pub fn get_invalid_token_key(token_id: &str) -> String {
     let mut key = INV_AUTH_TOKEN_REDIS_KEY.to_owned();
-    key.push_str(token);
+    key.push_str(token_id);
     key
 }
 
@@ -27,3 +95,83 @@ pub fn get_refresh_token_key(token: &str) -> String {
     key.push_str(token);
     key
 }
+
+pub fn get_session_key(session_id: &Uuid) -> String {
+    let mut key = SESSION_REDIS_KEY.to_owned();
+    key.push_str(&session_id.to_string());
+    key
+}
+
+pub fn get_user_sessions_key(user_id: &Uuid) -> String {
+    let mut key = USER_SESSIONS_REDIS_KEY.to_owned();
+    key.push_str(&user_id.to_string());
+    key
+}
+
+pub fn get_email_verification_token_key(token: &str) -> String {
+    let mut key = EMAIL_VERIFICATION_TOKEN_REDIS_KEY.to_owned();
+    key.push_str(token);
+    key
+}
+
+pub fn get_password_reset_token_key(token: &str) -> String {
+    let mut key = PASSWORD_RESET_TOKEN_REDIS_KEY.to_owned();
+    key.push_str(token);
+    key
+}
+
+pub fn get_email_link_token_key(token: &str) -> String {
+    let mut key = EMAIL_LINK_TOKEN_REDIS_KEY.to_owned();
+    key.push_str(token);
+    key
+}
+
+pub fn get_login_failures_key(subject: &str) -> String {
+    let mut key = LOGIN_FAILURES_REDIS_KEY.to_owned();
+    key.push_str(subject);
+    key
+}
+
+pub fn get_login_lockout_key(subject: &str) -> String {
+    let mut key = LOGIN_LOCKOUT_REDIS_KEY.to_owned();
+    key.push_str(subject);
+    key
+}
+
+pub fn get_two_factor_challenge_key(token: &str) -> String {
+    let mut key = TWO_FACTOR_CHALLENGE_REDIS_KEY.to_owned();
+    key.push_str(token);
+    key
+}
+
+pub fn get_two_factor_failures_key(user_id: &Uuid) -> String {
+    let mut key = TWO_FACTOR_FAILURES_REDIS_KEY.to_owned();
+    key.push_str(&user_id.to_string());
+    key
+}
+
+pub fn get_game_authorization_code_key(code: &str) -> String {
+    let mut key = GAME_AUTHORIZATION_CODE_REDIS_KEY.to_owned();
+    key.push_str(code);
+    key
+}
+
+pub fn get_game_tokens_key(user_id: &Uuid, game_id: &Uuid) -> String {
+    let mut key = GAME_TOKENS_REDIS_KEY.to_owned();
+    key.push_str(&user_id.to_string());
+    key.push(':');
+    key.push_str(&game_id.to_string());
+    key
+}
+
+pub fn get_guest_creation_key(ip: &str) -> String {
+    let mut key = GUEST_CREATION_REDIS_KEY.to_owned();
+    key.push_str(ip);
+    key
+}
+
+pub fn get_verification_resend_key(subject: &str) -> String {
+    let mut key = VERIFICATION_RESEND_REDIS_KEY.to_owned();
+    key.push_str(subject);
+    key
+}
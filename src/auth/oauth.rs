@@ -0,0 +1,115 @@
+use serde::Deserialize;
+use serde_json::Value;
+use webgame_collection_api_macros::Error;
+
+use crate::{
+    config::{OAuthProviderConfig, CONFIG},
+    schema::types::user::OAuthProvider,
+};
+
+lazy_static::lazy_static! {
+    static ref HTTP_CLIENT: reqwest::Client = reqwest::Client::new();
+}
+
+#[derive(Error)]
+pub enum OAuthError {
+    #[error(message = "OAuth provider not configured")]
+    ProviderNotConfigured,
+    #[error(message = "Request to the OAuth provider failed")]
+    RequestFailed(reqwest::Error),
+    #[error(message = "OAuth provider rejected the authorization code")]
+    CodeRejected,
+    #[error(message = "Provider user ID not found")]
+    IdentifierNotFound,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+}
+
+impl OAuthProvider {
+    fn config(&self) -> Option<&'static OAuthProviderConfig> {
+        match self {
+            OAuthProvider::Kakao => CONFIG.oauth.kakao.as_ref(),
+            OAuthProvider::Google => CONFIG.oauth.google.as_ref(),
+            OAuthProvider::Facebook => CONFIG.oauth.facebook.as_ref(),
+        }
+    }
+
+    fn default_token_url(&self) -> &'static str {
+        match self {
+            OAuthProvider::Kakao => "https://kauth.kakao.com/oauth/token",
+            OAuthProvider::Google => "https://oauth2.googleapis.com/token",
+            OAuthProvider::Facebook => "https://graph.facebook.com/oauth/access_token",
+        }
+    }
+
+    fn default_user_info_url(&self) -> &'static str {
+        match self {
+            OAuthProvider::Kakao => "https://kapi.kakao.com/v2/user/me",
+            OAuthProvider::Google => "https://openidconnect.googleapis.com/v1/userinfo",
+            OAuthProvider::Facebook => "https://graph.facebook.com/me",
+        }
+    }
+
+    fn identifier_field(&self) -> &'static str {
+        match self {
+            OAuthProvider::Google => "sub",
+            OAuthProvider::Kakao | OAuthProvider::Facebook => "id",
+        }
+    }
+}
+
+pub async fn exchange_code(provider: OAuthProvider, code: &str) -> Result<String, OAuthError> {
+    let config = provider.config().ok_or(OAuthError::ProviderNotConfigured)?;
+
+    let token_response = HTTP_CLIENT
+        .post(
+            config
+                .token_url
+                .as_deref()
+                .unwrap_or_else(|| provider.default_token_url()),
+        )
+        .form(&[
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("client_id", &config.client_id),
+            ("client_secret", &config.client_secret),
+            ("redirect_uri", &config.redirect_uri),
+        ])
+        .send()
+        .await
+        .map_err(OAuthError::RequestFailed)?;
+
+    if !token_response.status().is_success() {
+        return Err(OAuthError::CodeRejected);
+    }
+
+    let token = token_response
+        .json::<TokenResponse>()
+        .await
+        .map_err(OAuthError::RequestFailed)?;
+
+    let user_info = HTTP_CLIENT
+        .get(
+            config
+                .user_info_url
+                .as_deref()
+                .unwrap_or_else(|| provider.default_user_info_url()),
+        )
+        .bearer_auth(&token.access_token)
+        .send()
+        .await
+        .and_then(|res| res.error_for_status())
+        .map_err(OAuthError::RequestFailed)?
+        .json::<Value>()
+        .await
+        .map_err(OAuthError::RequestFailed)?;
+
+    match user_info.get(provider.identifier_field()) {
+        Some(Value::String(id)) => Ok(id.clone()),
+        Some(Value::Number(id)) => Ok(id.to_string()),
+        _ => Err(OAuthError::IdentifierNotFound),
+    }
+}
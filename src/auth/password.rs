@@ -0,0 +1,205 @@
+use std::str::FromStr;
+
+use chrono::{Duration, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+use webgame_collection_api_macros::Error;
+
+use crate::{
+    config::CONFIG,
+    mail::{Mail, MailError, Mailer},
+};
+
+use super::{
+    create_random_token, get_password_reset_token_key,
+    password_data::{PasswordData, PasswordEncryptionError},
+    refresh::revoke_user_refresh_tokens,
+    AuthMethodType,
+};
+
+#[derive(Error)]
+pub enum PasswordError {
+    #[error(message = "Database error")]
+    DbError(sqlx::Error),
+    #[error(message = "Redis error")]
+    RedisError(redis::RedisError),
+    #[error(message = "Mail sending failed")]
+    MailError(MailError),
+    #[error(message = "Password encryption failed")]
+    EncryptionFailed(PasswordEncryptionError),
+    #[error(message = "No password based auth method found")]
+    MethodNotFound,
+    #[error(message = "Invalid auth method data detected")]
+    InvalidMethodData,
+    #[error(message = "Wrong password")]
+    WrongPassword,
+    #[error(message = "Token creation failed")]
+    TokenCreationFailed,
+    #[error(message = "Invalid or expired password reset token")]
+    InvalidToken,
+}
+
+// 비밀번호 로그인 수단이 없는 계정은 None
+pub async fn verify_user_password(
+    pool: &PgPool,
+    user_id: &Uuid,
+    password: &str,
+) -> Result<Option<bool>, PasswordError> {
+    let methods = sqlx::query!(
+        r#"
+        SELECT extra_info FROM public.user_auth_method
+        WHERE user_id = $1 AND type = $2
+        "#,
+        user_id,
+        AuthMethodType::Email as AuthMethodType,
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(PasswordError::DbError)?;
+
+    if methods.is_empty() {
+        return Ok(None);
+    }
+
+    let mut verified = false;
+    for method in methods {
+        let password_data: PasswordData = method
+            .extra_info
+            .and_then(|data| serde_json::from_value(data).ok())
+            .ok_or(PasswordError::InvalidMethodData)?;
+
+        verified |= password_data.verify(password);
+    }
+
+    Ok(Some(verified))
+}
+
+pub async fn change_password(
+    pool: &PgPool,
+    redis_conn: &mut deadpool_redis::Connection,
+    user_id: &Uuid,
+    old_password: &str,
+    new_password: &str,
+) -> Result<(), PasswordError> {
+    match verify_user_password(pool, user_id, old_password).await? {
+        Some(true) => {}
+        Some(false) => return Err(PasswordError::WrongPassword),
+        None => return Err(PasswordError::MethodNotFound),
+    }
+
+    set_password(pool, redis_conn, user_id, new_password).await
+}
+
+pub async fn request_password_reset(
+    pool: &PgPool,
+    mailer: &dyn Mailer,
+    redis_conn: &mut deadpool_redis::Connection,
+    email: &str,
+) -> Result<(), PasswordError> {
+    let method = sqlx::query!(
+        r#"
+        SELECT m.user_id FROM public.user_auth_method m
+        JOIN public.user u ON u.id = m.user_id
+        WHERE m.type = $1 AND m.identifier = $2
+            AND u.email_verified_at IS NOT NULL AND u.deleted_at IS NULL
+        "#,
+        AuthMethodType::Email as AuthMethodType,
+        email,
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(PasswordError::DbError)?;
+
+    // 가입 여부가 노출되지 않도록 계정이 없거나 인증되지 않은 주소인 경우에도 성공으로 처리
+    let user_id = match method {
+        Some(method) => method.user_id,
+        None => return Ok(()),
+    };
+
+    let token = create_random_token().ok_or(PasswordError::TokenCreationFailed)?;
+    let key = get_password_reset_token_key(&token);
+    let expire_at = (Utc::now() + Duration::seconds(CONFIG.password_reset_ttl))
+        .timestamp()
+        .to_string();
+
+    redis::pipe()
+        .cmd("SET")
+        .arg(&[&key, &user_id.to_string()])
+        .ignore()
+        .cmd("EXPIREAT")
+        .arg(&[&key, &expire_at])
+        .query_async::<_, ()>(redis_conn)
+        .await
+        .map_err(PasswordError::RedisError)?;
+
+    mailer
+        .send(Mail {
+            to: email.to_owned(),
+            subject: "Reset your password".to_owned(),
+            body: format!("Use the following token to reset your password: {}", token),
+        })
+        .await
+        .map_err(PasswordError::MailError)
+}
+
+pub async fn reset_password(
+    pool: &PgPool,
+    redis_conn: &mut deadpool_redis::Connection,
+    token: &str,
+    new_password: &str,
+) -> Result<(), PasswordError> {
+    let key = get_password_reset_token_key(token);
+
+    let (user_id,): (Option<String>,) = redis::pipe()
+        .atomic()
+        .cmd("GET")
+        .arg(&key)
+        .cmd("DEL")
+        .arg(&key)
+        .ignore()
+        .query_async(redis_conn)
+        .await
+        .map_err(PasswordError::RedisError)?;
+
+    let user_id = user_id
+        .and_then(|user_id| Uuid::from_str(&user_id).ok())
+        .ok_or(PasswordError::InvalidToken)?;
+
+    set_password(pool, redis_conn, &user_id, new_password).await
+}
+
+async fn set_password(
+    pool: &PgPool,
+    redis_conn: &mut deadpool_redis::Connection,
+    user_id: &Uuid,
+    password: &str,
+) -> Result<(), PasswordError> {
+    let password_data =
+        PasswordData::new(password, CONFIG.pbkdf2_salt_size, CONFIG.password_params())
+            .map_err(PasswordError::EncryptionFailed)?;
+    let data = serde_json::to_value(password_data).map_err(|_| PasswordError::InvalidMethodData)?;
+
+    let result = sqlx::query!(
+        r#"
+        UPDATE public.user_auth_method m
+        SET extra_info = $3
+        FROM public.user u
+        WHERE m.user_id = $1 AND m.type = $2
+            AND u.id = m.user_id AND u.deleted_at IS NULL
+        "#,
+        user_id,
+        AuthMethodType::Email as AuthMethodType,
+        data,
+    )
+    .execute(pool)
+    .await
+    .map_err(PasswordError::DbError)?;
+
+    if result.rows_affected() == 0 {
+        return Err(PasswordError::MethodNotFound);
+    }
+
+    revoke_user_refresh_tokens(user_id, redis_conn)
+        .await
+        .map_err(PasswordError::RedisError)
+}
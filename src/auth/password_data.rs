@@ -1,67 +1,193 @@
 use std::num::NonZeroU32;
 
+use argon2::{Argon2, Params, Version};
 use ring::{
-    digest,
+    constant_time, digest,
     pbkdf2::{self, PBKDF2_HMAC_SHA512},
     rand::{SecureRandom, SystemRandom},
 };
 use serde::{Deserialize, Serialize};
 use webgame_collection_api_macros::Error;
 
+const ARGON2_OUTPUT_LEN: usize = 32;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum PasswordAlgorithm {
+    Pbkdf2,
+    Argon2id,
+}
+
+impl Default for PasswordAlgorithm {
+    // 알고리즘 태그가 없는 기존 데이터는 모두 PBKDF2로 생성됨
+    fn default() -> Self {
+        PasswordAlgorithm::Pbkdf2
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PasswordParams {
+    Pbkdf2 {
+        iterations: NonZeroU32,
+    },
+    Argon2id {
+        iterations: NonZeroU32,
+        memory_cost: u32,
+        parallelism: u32,
+    },
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct PasswordData {
+    #[serde(default)]
+    algorithm: PasswordAlgorithm,
     hash: String,
     salt: String,
     iterations: NonZeroU32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    memory_cost: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    parallelism: Option<u32>,
 }
 
 #[derive(Error)]
 pub enum PasswordEncryptionError {
     #[error(message = "Failed to create salt")]
     SaltCreationFailed,
+    #[error(message = "Invalid password hashing parameters")]
+    InvalidParams,
 }
 
 impl PasswordData {
     pub fn new(
         password: &str,
         salt_size: usize,
-        iterations: NonZeroU32,
+        params: PasswordParams,
     ) -> Result<PasswordData, PasswordEncryptionError> {
         let salt = PasswordData::salt(salt_size)
             .map_err(|_| PasswordEncryptionError::SaltCreationFailed)?;
-        let mut hash = [0; digest::SHA512_OUTPUT_LEN];
-        pbkdf2::derive(
-            PBKDF2_HMAC_SHA512,
-            iterations,
-            &salt,
-            password.as_bytes(),
-            &mut hash,
-        );
-
-        Ok(PasswordData {
-            hash: base64::encode(hash),
-            salt: base64::encode(salt),
-            iterations,
-        })
+
+        let data = match params {
+            PasswordParams::Pbkdf2 { iterations } => {
+                let mut hash = [0; digest::SHA512_OUTPUT_LEN];
+                pbkdf2::derive(
+                    PBKDF2_HMAC_SHA512,
+                    iterations,
+                    &salt,
+                    password.as_bytes(),
+                    &mut hash,
+                );
+
+                PasswordData {
+                    algorithm: PasswordAlgorithm::Pbkdf2,
+                    hash: base64::encode(hash),
+                    salt: base64::encode(salt),
+                    iterations,
+                    memory_cost: None,
+                    parallelism: None,
+                }
+            }
+            PasswordParams::Argon2id {
+                iterations,
+                memory_cost,
+                parallelism,
+            } => {
+                let mut hash = [0; ARGON2_OUTPUT_LEN];
+                PasswordData::argon2id(iterations, memory_cost, parallelism)
+                    .and_then(|argon2| {
+                        argon2
+                            .hash_password_into(password.as_bytes(), &salt, &mut hash)
+                            .ok()
+                    })
+                    .ok_or(PasswordEncryptionError::InvalidParams)?;
+
+                PasswordData {
+                    algorithm: PasswordAlgorithm::Argon2id,
+                    hash: base64::encode(hash),
+                    salt: base64::encode(salt),
+                    iterations,
+                    memory_cost: Some(memory_cost),
+                    parallelism: Some(parallelism),
+                }
+            }
+        };
+
+        Ok(data)
     }
 
     pub fn verify(&self, password: &str) -> bool {
         let salt_result = base64::decode(&self.salt);
         let hash_result = base64::decode(&self.hash);
 
-        match (salt_result, hash_result) {
-            (Ok(salt), Ok(hash)) => pbkdf2::verify(
+        match (self.params(), salt_result, hash_result) {
+            (Some(PasswordParams::Pbkdf2 { iterations }), Ok(salt), Ok(hash)) => pbkdf2::verify(
                 PBKDF2_HMAC_SHA512,
-                self.iterations,
+                iterations,
                 salt.as_slice(),
                 password.as_bytes(),
                 hash.as_slice(),
             )
             .is_ok(),
+            (
+                Some(PasswordParams::Argon2id {
+                    iterations,
+                    memory_cost,
+                    parallelism,
+                }),
+                Ok(salt),
+                Ok(hash),
+            ) => {
+                let mut derived = vec![0; hash.len()];
+                PasswordData::argon2id(iterations, memory_cost, parallelism)
+                    .and_then(|argon2| {
+                        argon2
+                            .hash_password_into(password.as_bytes(), &salt, &mut derived)
+                            .ok()
+                    })
+                    .map(|_| constant_time::verify_slices_are_equal(&derived, &hash).is_ok())
+                    .unwrap_or(false)
+            }
             _ => false,
         }
     }
 
+    pub fn needs_rehash(&self, params: PasswordParams) -> bool {
+        self.params() != Some(params)
+    }
+
+    fn params(&self) -> Option<PasswordParams> {
+        match self.algorithm {
+            PasswordAlgorithm::Pbkdf2 => Some(PasswordParams::Pbkdf2 {
+                iterations: self.iterations,
+            }),
+            PasswordAlgorithm::Argon2id => Some(PasswordParams::Argon2id {
+                iterations: self.iterations,
+                memory_cost: self.memory_cost?,
+                parallelism: self.parallelism?,
+            }),
+        }
+    }
+
+    fn argon2id(
+        iterations: NonZeroU32,
+        memory_cost: u32,
+        parallelism: u32,
+    ) -> Option<Argon2<'static>> {
+        let params = Params::new(
+            memory_cost,
+            iterations.get(),
+            parallelism,
+            Some(ARGON2_OUTPUT_LEN),
+        )
+        .ok()?;
+
+        Some(Argon2::new(
+            argon2::Algorithm::Argon2id,
+            Version::V0x13,
+            params,
+        ))
+    }
+
     fn salt(size: usize) -> Result<Vec<u8>, ring::error::Unspecified> {
         let mut salt = vec![0; size];
         let rng = SystemRandom::new();
@@ -70,3 +196,87 @@ impl PasswordData {
         Ok(salt)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SALT_SIZE: usize = 16;
+
+    fn pbkdf2_params(iterations: u32) -> PasswordParams {
+        PasswordParams::Pbkdf2 {
+            iterations: NonZeroU32::new(iterations).unwrap(),
+        }
+    }
+
+    // 테스트가 빨리 끝나도록 허용되는 최소 수준의 비용을 사용
+    fn argon2id_params(memory_cost: u32) -> PasswordParams {
+        PasswordParams::Argon2id {
+            iterations: NonZeroU32::new(1).unwrap(),
+            memory_cost,
+            parallelism: 1,
+        }
+    }
+
+    #[test]
+    fn verifies_pbkdf2_password() {
+        let data = PasswordData::new("password", SALT_SIZE, pbkdf2_params(1000)).unwrap();
+
+        assert!(data.verify("password"));
+        assert!(!data.verify("Password"));
+        assert!(!data.verify(""));
+    }
+
+    #[test]
+    fn verifies_argon2id_password() {
+        let data = PasswordData::new("password", SALT_SIZE, argon2id_params(64)).unwrap();
+
+        assert!(data.verify("password"));
+        assert!(!data.verify("Password"));
+        assert!(!data.verify(""));
+    }
+
+    #[test]
+    fn rejects_invalid_argon2id_params() {
+        assert!(matches!(
+            PasswordData::new("password", SALT_SIZE, argon2id_params(0)),
+            Err(PasswordEncryptionError::InvalidParams)
+        ));
+    }
+
+    #[test]
+    fn reads_legacy_data_without_algorithm_as_pbkdf2() {
+        let data = PasswordData::new("password", SALT_SIZE, pbkdf2_params(1000)).unwrap();
+        let mut value = serde_json::to_value(&data).unwrap();
+        value.as_object_mut().unwrap().remove("algorithm");
+
+        let data: PasswordData = serde_json::from_value(value).unwrap();
+        assert_eq!(data.algorithm, PasswordAlgorithm::Pbkdf2);
+        assert!(data.verify("password"));
+        assert!(!data.needs_rehash(pbkdf2_params(1000)));
+    }
+
+    #[test]
+    fn needs_rehash_when_params_differ() {
+        let pbkdf2 = PasswordData::new("password", SALT_SIZE, pbkdf2_params(1000)).unwrap();
+        assert!(!pbkdf2.needs_rehash(pbkdf2_params(1000)));
+        assert!(pbkdf2.needs_rehash(pbkdf2_params(2000)));
+        assert!(pbkdf2.needs_rehash(argon2id_params(64)));
+
+        let argon2id = PasswordData::new("password", SALT_SIZE, argon2id_params(64)).unwrap();
+        assert!(!argon2id.needs_rehash(argon2id_params(64)));
+        assert!(argon2id.needs_rehash(argon2id_params(128)));
+        assert!(argon2id.needs_rehash(pbkdf2_params(1000)));
+    }
+
+    #[test]
+    fn rejects_argon2id_data_missing_params() {
+        let data = PasswordData::new("password", SALT_SIZE, argon2id_params(64)).unwrap();
+        let mut value = serde_json::to_value(&data).unwrap();
+        value.as_object_mut().unwrap().remove("memory_cost");
+
+        let data: PasswordData = serde_json::from_value(value).unwrap();
+        assert!(!data.verify("password"));
+        assert!(data.needs_rehash(argon2id_params(64)));
+    }
+}
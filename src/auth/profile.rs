@@ -0,0 +1,219 @@
+use async_graphql::MaybeUndefined;
+use chrono::{Duration, Utc};
+use reqwest::Url;
+use sqlx::PgPool;
+use uuid::Uuid;
+use webgame_collection_api_macros::Error;
+
+use crate::{
+    auth::{
+        password::{verify_user_password, PasswordError},
+        AuthMethodType,
+    },
+    config::CONFIG,
+    schema::types::{
+        node::{IdData, NodeIdent},
+        scalars::DateTimeScalar,
+        user::{UpdateProfileInput, User},
+    },
+};
+
+const MAX_BIO_LENGTH: usize = 500;
+
+pub struct ProfileUpdate {
+    pub user: User,
+    // 인증을 기다리는 새 이메일 주소
+    pub pending_email: Option<String>,
+}
+
+#[derive(Error)]
+pub enum ProfileError {
+    #[error(message = "Database error")]
+    DbError(sqlx::Error),
+    #[error(message = "User not found")]
+    UserNotFound,
+    #[error(message = "Nickname already taken")]
+    NicknameTaken,
+    #[error(message = "Email already taken")]
+    EmailTaken,
+    #[error(message = "Nickname can be changed again in {0} seconds")]
+    NicknameChangeCooldown(i64),
+    #[error(message = "Avatar URL must be an absolute http(s) URL")]
+    InvalidAvatarUrl,
+    #[error(message = "Bio is too long")]
+    BioTooLong,
+    #[error(message = "Guest accounts cannot set an email address")]
+    GuestAccount,
+    #[error(message = "The current password is required to change the email")]
+    CurrentPasswordRequired,
+    #[error(message = "Wrong password")]
+    WrongPassword,
+    #[error(message = "Password verification failed")]
+    PasswordError(PasswordError),
+}
+
+pub async fn update_profile(
+    pool: &PgPool,
+    user_id: &Uuid,
+    input: UpdateProfileInput,
+) -> Result<ProfileUpdate, ProfileError> {
+    let mut tx = pool.begin().await.map_err(ProfileError::DbError)?;
+
+    let current = sqlx::query!(
+        r#"
+        SELECT * FROM public.user
+        WHERE id = $1 AND deleted_at IS NULL
+        FOR UPDATE
+        "#,
+        user_id,
+    )
+    .fetch_optional(&mut tx)
+    .await
+    .map_err(ProfileError::DbError)?
+    .ok_or(ProfileError::UserNotFound)?;
+
+    let new_nickname = input.nickname.filter(|n| *n != current.nickname);
+    let new_email = input.email.filter(|e| *e != current.email);
+
+    if new_email.is_some() && current.is_guest {
+        return Err(ProfileError::GuestAccount);
+    }
+
+    // 탈취된 access token만으로 로그인 이메일을 바꿀 수 없도록 비밀번호를 다시 확인
+    if new_email.is_some() {
+        let password = input.current_password.as_deref().unwrap_or_default();
+        match verify_user_password(pool, user_id, password)
+            .await
+            .map_err(ProfileError::PasswordError)?
+        {
+            // 비밀번호가 없는 OAuth 계정은 확인할 수단이 없음
+            None | Some(true) => {}
+            Some(false) if input.current_password.is_none() => {
+                return Err(ProfileError::CurrentPasswordRequired)
+            }
+            Some(false) => return Err(ProfileError::WrongPassword),
+        }
+    }
+
+    if new_nickname.is_some() {
+        if let Some(changed_at) = current.nickname_changed_at {
+            let available_at = changed_at + Duration::seconds(CONFIG.nickname_change_cooldown);
+            let remaining = available_at.signed_duration_since(Utc::now()).num_seconds();
+            if remaining > 0 {
+                return Err(ProfileError::NicknameChangeCooldown(remaining));
+            }
+        }
+    }
+
+    if new_nickname.is_some() || new_email.is_some() {
+        let conflicts = sqlx::query!(
+            r#"
+            SELECT nickname, email FROM public.user
+            WHERE id <> $1 AND (nickname = $2 OR email = $3)
+            "#,
+            user_id,
+            new_nickname,
+            new_email,
+        )
+        .fetch_all(&mut tx)
+        .await
+        .map_err(ProfileError::DbError)?;
+
+        if conflicts
+            .iter()
+            .any(|u| Some(&u.nickname) == new_nickname.as_ref())
+        {
+            return Err(ProfileError::NicknameTaken);
+        }
+        if conflicts
+            .iter()
+            .any(|u| Some(&u.email) == new_email.as_ref())
+        {
+            return Err(ProfileError::EmailTaken);
+        }
+    }
+
+    // 이메일 로그인 수단의 식별자도 함께 바뀌므로 다른 유저의 로그인 수단과도 겹치지 않아야 함
+    if let Some(email) = &new_email {
+        let existing_method = sqlx::query!(
+            r#"
+            SELECT user_id FROM public.user_auth_method
+            WHERE type = $1 AND identifier = $2 AND user_id <> $3
+            "#,
+            AuthMethodType::Email as AuthMethodType,
+            email,
+            user_id,
+        )
+        .fetch_optional(&mut tx)
+        .await
+        .map_err(ProfileError::DbError)?;
+
+        if existing_method.is_some() {
+            return Err(ProfileError::EmailTaken);
+        }
+    }
+
+    let avatar_url = match input.avatar_url {
+        MaybeUndefined::Undefined => current.avatar_url,
+        MaybeUndefined::Null => None,
+        MaybeUndefined::Value(url) => {
+            match Url::parse(&url).map(|parsed| parsed.scheme().to_owned()) {
+                Ok(scheme) if scheme == "http" || scheme == "https" => Some(url),
+                _ => return Err(ProfileError::InvalidAvatarUrl),
+            }
+        }
+    };
+    let bio = match input.bio {
+        MaybeUndefined::Undefined => current.bio,
+        MaybeUndefined::Null => None,
+        MaybeUndefined::Value(bio) if bio.chars().count() > MAX_BIO_LENGTH => {
+            return Err(ProfileError::BioTooLong)
+        }
+        MaybeUndefined::Value(bio) => Some(bio),
+    };
+
+    // 새 이메일은 인증을 마칠 때까지 보류해 두고, 로그인 이메일은 인증 후에 바뀜
+    let user = sqlx::query!(
+        r#"
+        UPDATE public.user
+        SET nickname = COALESCE($2, nickname),
+            nickname_changed_at = CASE WHEN $2::text IS NULL
+                THEN nickname_changed_at ELSE CURRENT_TIMESTAMP END,
+            pending_email = COALESCE($3, pending_email),
+            avatar_url = $4,
+            bio = $5
+        WHERE id = $1
+        RETURNING *
+        "#,
+        user_id,
+        new_nickname,
+        new_email,
+        avatar_url,
+        bio,
+    )
+    .fetch_one(&mut tx)
+    .await
+    .map_err(ProfileError::DbError)?;
+
+    tx.commit().await.map_err(ProfileError::DbError)?;
+
+    Ok(ProfileUpdate {
+        user: User {
+            id: IdData {
+                ty: NodeIdent::User,
+                uuid: user.id,
+            }
+            .to_id_scalar(),
+            uuid: user.id,
+            nickname: user.nickname,
+            email: user.email,
+            email_verified: user.email_verified_at.is_some(),
+            avatar_url: user.avatar_url,
+            bio: user.bio,
+            is_guest: user.is_guest,
+            registered_at: DateTimeScalar(user.registered_at),
+            deleted_at: user.deleted_at.map(DateTimeScalar),
+        },
+        pending_email: new_email,
+    })
+}
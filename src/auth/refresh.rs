@@ -1,12 +1,38 @@
 use chrono::{Duration, TimeZone, Utc};
 use ring::rand::{SecureRandom, SystemRandom};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use webgame_collection_api_macros::Error;
 
-use super::get_refresh_token_key;
+use crate::config::CONFIG;
 
-pub enum RefreshCheckResult {
-    OnlyAccessToken,
-    Both,
-    None,
+use super::{
+    client_info::ClientInfo, get_refresh_token_key, get_session_key, get_user_sessions_key,
+};
+
+#[derive(Serialize, Deserialize)]
+struct RefreshTokenData {
+    user_id: Uuid,
+    session_id: Uuid,
+    expire_at: i64,
+}
+
+pub struct RotatedRefreshToken {
+    pub user_id: Uuid,
+    pub session_id: Uuid,
+    pub refresh_token: String,
+}
+
+#[derive(Error)]
+pub enum RefreshError {
+    #[error(message = "Redis error")]
+    RedisError(redis::RedisError),
+    #[error(message = "Invalid refresh token")]
+    InvalidToken,
+    #[error(message = "Refresh token reuse detected")]
+    TokenReused,
+    #[error(message = "Token creation failed")]
+    TokenCreationFailed,
 }
 
 pub fn create_refresh_token(size: usize) -> Option<String> {
@@ -17,41 +43,188 @@ pub fn create_refresh_token(size: usize) -> Option<String> {
     Some(base64::encode(&buf))
 }
 
+// 하나의 로그인에서 이어지는 refresh token들은 같은 세션(토큰 패밀리)에 속하며,
+// 세션 레코드에는 현재 유효한 토큰 하나만 기록됨
 pub async fn register_refresh_token(
     refresh_token: &str,
+    user_id: &Uuid,
+    client_info: &ClientInfo,
     redis_conn: &mut deadpool_redis::Connection,
-) -> Result<(), redis::RedisError> {
-    let key = get_refresh_token_key(refresh_token);
-    let expire_at = (Utc::now() + Duration::days(30)).timestamp().to_string();
+) -> Result<Uuid, redis::RedisError> {
+    let now = Utc::now();
+    let data = RefreshTokenData {
+        user_id: *user_id,
+        session_id: Uuid::new_v4(),
+        expire_at: (now + Duration::seconds(CONFIG.refresh_token_lifetime)).timestamp(),
+    };
+
+    store_refresh_token(refresh_token, &data, redis_conn).await?;
+
+    let session_key = get_session_key(&data.session_id);
+    let user_key = get_user_sessions_key(user_id);
+
+    let mut fields = vec![
+        ("token", refresh_token.to_owned()),
+        ("user_id", user_id.to_string()),
+        ("created_at", now.timestamp().to_string()),
+        ("last_refreshed_at", now.timestamp().to_string()),
+    ];
+    if let Some(ip) = &client_info.ip {
+        fields.push(("ip", ip.clone()));
+    }
+    if let Some(user_agent) = &client_info.user_agent {
+        fields.push(("user_agent", user_agent.clone()));
+    }
 
     redis::pipe()
-        .cmd("SET")
-        .arg(&[&key, &expire_at])
+        .cmd("HSET")
+        .arg(&session_key)
+        .arg(fields)
         .ignore()
         .cmd("EXPIREAT")
-        .arg(&[&key, &expire_at])
-        .query_async(redis_conn)
-        .await
+        .arg(&session_key)
+        .arg(data.expire_at)
+        .ignore()
+        .cmd("SADD")
+        .arg(&user_key)
+        .arg(data.session_id.to_string())
+        .ignore()
+        .cmd("EXPIREAT")
+        .arg(&user_key)
+        .arg(data.expire_at)
+        .query_async::<_, ()>(redis_conn)
+        .await?;
+
+    Ok(data.session_id)
 }
 
-pub async fn check_refresh(
+pub async fn rotate_refresh_token(
     refresh_token: &str,
     redis_conn: &mut deadpool_redis::Connection,
-) -> Result<RefreshCheckResult, redis::RedisError> {
-    let result = redis::cmd("GET")
+) -> Result<RotatedRefreshToken, RefreshError> {
+    let data = redis::cmd("GET")
         .arg(get_refresh_token_key(refresh_token))
-        .query_async::<_, Option<i64>>(redis_conn)
-        .await?;
+        .query_async::<_, Option<String>>(redis_conn)
+        .await
+        .map_err(RefreshError::RedisError)?
+        .and_then(|data| serde_json::from_str::<RefreshTokenData>(&data).ok())
+        .ok_or(RefreshError::InvalidToken)?;
+
+    let new_token =
+        create_refresh_token(CONFIG.refresh_token_size).ok_or(RefreshError::TokenCreationFailed)?;
+
+    // 만료까지 갱신 기간 이상 남았다면 기존 만료 시각을 유지하고,
+    // 그렇지 않다면 새로 유효 기간을 부여
+    let now = Utc::now();
+    let renewed = Utc.timestamp(data.expire_at, 0).signed_duration_since(now)
+        < Duration::seconds(CONFIG.refresh_token_renewal_window);
+    let expire_at = match renewed {
+        true => (now + Duration::seconds(CONFIG.refresh_token_lifetime)).timestamp(),
+        false => data.expire_at,
+    };
+
+    // 세션의 현재 토큰이 제시된 토큰과 일치할 때만 교체해서,
+    // 이미 사용된 토큰이 다시 제시되거나 동시에 사용된 경우를 탐지
+    let rotated = redis::Script::new(
+        r"
+        if redis.call('HGET', KEYS[1], 'token') == ARGV[1] then
+            redis.call('HSET', KEYS[1], 'token', ARGV[2], 'last_refreshed_at', ARGV[3])
+            redis.call('EXPIREAT', KEYS[1], ARGV[4])
+            return 1
+        else
+            return 0
+        end
+        ",
+    )
+    .key(get_session_key(&data.session_id))
+    .arg(refresh_token)
+    .arg(&new_token)
+    .arg(now.timestamp())
+    .arg(expire_at)
+    .invoke_async::<_, bool>(redis_conn)
+    .await
+    .map_err(RefreshError::RedisError)?;
+
+    if !rotated {
+        revoke_refresh_token_family(&data.user_id, &data.session_id, redis_conn)
+            .await
+            .map_err(RefreshError::RedisError)?;
+
+        return Err(RefreshError::TokenReused);
+    }
+
+    let new_data = RefreshTokenData { expire_at, ..data };
+    store_refresh_token(&new_token, &new_data, redis_conn)
+        .await
+        .map_err(RefreshError::RedisError)?;
+
+    if renewed {
+        redis::cmd("EXPIREAT")
+            .arg(get_user_sessions_key(&new_data.user_id))
+            .arg(expire_at)
+            .query_async::<_, ()>(redis_conn)
+            .await
+            .map_err(RefreshError::RedisError)?;
+    }
 
-    if let Some(expire_at) = result {
-        let expire_at = Utc.timestamp(expire_at, 0);
-        let diff = expire_at.signed_duration_since(Utc::now());
+    Ok(RotatedRefreshToken {
+        user_id: new_data.user_id,
+        session_id: new_data.session_id,
+        refresh_token: new_token,
+    })
+}
 
-        match diff < Duration::days(10) {
-            true => Ok(RefreshCheckResult::Both),
-            false => Ok(RefreshCheckResult::OnlyAccessToken),
+pub async fn revoke_refresh_token_family(
+    user_id: &Uuid,
+    session_id: &Uuid,
+    redis_conn: &mut deadpool_redis::Connection,
+) -> Result<(), redis::RedisError> {
+    redis::pipe()
+        .cmd("DEL")
+        .arg(get_session_key(session_id))
+        .ignore()
+        .cmd("SREM")
+        .arg(get_user_sessions_key(user_id))
+        .arg(session_id.to_string())
+        .query_async(redis_conn)
+        .await
+}
+
+pub async fn revoke_user_refresh_tokens(
+    user_id: &Uuid,
+    redis_conn: &mut deadpool_redis::Connection,
+) -> Result<(), redis::RedisError> {
+    let user_key = get_user_sessions_key(user_id);
+    let session_ids = redis::cmd("SMEMBERS")
+        .arg(&user_key)
+        .query_async::<_, Vec<String>>(redis_conn)
+        .await?;
+
+    let mut pipe = redis::pipe();
+    for session_id in session_ids {
+        if let Ok(session_id) = Uuid::parse_str(&session_id) {
+            pipe.cmd("DEL").arg(get_session_key(&session_id)).ignore();
         }
-    } else {
-        Ok(RefreshCheckResult::None)
     }
+    pipe.cmd("DEL").arg(&user_key).query_async(redis_conn).await
+}
+
+async fn store_refresh_token(
+    refresh_token: &str,
+    data: &RefreshTokenData,
+    redis_conn: &mut deadpool_redis::Connection,
+) -> Result<(), redis::RedisError> {
+    let key = get_refresh_token_key(refresh_token);
+    let value = serde_json::to_string(data).unwrap_or_default();
+
+    redis::pipe()
+        .cmd("SET")
+        .arg(&key)
+        .arg(value)
+        .ignore()
+        .cmd("EXPIREAT")
+        .arg(&key)
+        .arg(data.expire_at)
+        .query_async(redis_conn)
+        .await
 }
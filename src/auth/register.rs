@@ -15,6 +15,8 @@ pub enum RegistrationError {
     DbError(sqlx::Error),
     #[error(message = "User already exists")]
     UserAlreadyExists,
+    #[error(message = "Auth method already registered")]
+    MethodAlreadyRegistered,
     #[error(message = "Password data not present")]
     PasswordDataNotPresent,
     #[error(message = "Password data invalid")]
@@ -48,6 +50,22 @@ pub async fn register(
         return Err(RegistrationError::UserAlreadyExists);
     }
 
+    let existing_method = sqlx::query!(
+        r#"
+        SELECT user_id FROM public.user_auth_method
+        WHERE type = $1 AND identifier = $2
+        "#,
+        &auth_type as &AuthMethodType,
+        identifier,
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(RegistrationError::DbError)?;
+
+    if existing_method.is_some() {
+        return Err(RegistrationError::MethodAlreadyRegistered);
+    }
+
     let data = match password_data {
         Some(data) => match serde_json::to_value(data) {
             Ok(data) => Ok(Some(data)),
@@ -93,8 +111,13 @@ pub async fn register(
             uuid: user.id,
         }
         .to_id_scalar(),
+        uuid: user.id,
         nickname: user.nickname,
         email: user.email,
+        email_verified: user.email_verified_at.is_some(),
+        avatar_url: user.avatar_url,
+        bio: user.bio,
+        is_guest: user.is_guest,
         registered_at: DateTimeScalar(user.registered_at),
         deleted_at: user.deleted_at.map(DateTimeScalar),
     })
@@ -0,0 +1,79 @@
+use async_graphql::{Context, Enum, Guard};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::error::Error;
+
+use super::auth_info::{AuthError, AuthInfo};
+
+#[derive(sqlx::Type, Enum, Serialize, Deserialize, Copy, Clone, Eq, PartialEq, Debug)]
+#[sqlx(type_name = "user_role_type", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    Admin,
+    Moderator,
+    Developer,
+}
+
+pub struct RoleGuard {
+    pub role: Role,
+}
+
+impl RoleGuard {
+    pub fn new(role: Role) -> RoleGuard {
+        RoleGuard { role }
+    }
+}
+
+#[async_trait]
+impl Guard for RoleGuard {
+    async fn check(&self, ctx: &Context<'_>) -> async_graphql::Result<()> {
+        let auth_info = ctx.data::<AuthInfo>()?;
+
+        auth_info.check_role(self.role).map_err(|e| e.build())
+    }
+}
+
+pub async fn get_user_roles(pool: &PgPool, user_id: &Uuid) -> Result<Vec<Role>, sqlx::Error> {
+    sqlx::query!(
+        r#"
+        SELECT role AS "role: Role" FROM public.user_role
+        WHERE user_id = $1
+        "#,
+        user_id,
+    )
+    .fetch_all(pool)
+    .await
+    .map(|records| records.into_iter().map(|r| r.role).collect())
+}
+
+pub async fn grant_role(pool: &PgPool, user_id: &Uuid, role: Role) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        INSERT INTO public.user_role (user_id, role)
+        VALUES ($1, $2)
+        ON CONFLICT DO NOTHING
+        "#,
+        user_id,
+        role as Role,
+    )
+    .execute(pool)
+    .await
+    .map(|_| ())
+}
+
+pub async fn revoke_role(pool: &PgPool, user_id: &Uuid, role: Role) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        DELETE FROM public.user_role
+        WHERE user_id = $1 AND role = $2
+        "#,
+        user_id,
+        role as Role,
+    )
+    .execute(pool)
+    .await
+    .map(|_| ())
+}
@@ -0,0 +1,52 @@
+use std::{fmt, str::FromStr};
+
+use async_graphql::Enum;
+use serde::{Deserialize, Serialize};
+
+#[derive(Enum, Serialize, Deserialize, Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Scope {
+    #[serde(rename = "profile:read")]
+    ProfileRead,
+    #[serde(rename = "chat:read")]
+    ChatRead,
+    #[serde(rename = "chat:write")]
+    ChatWrite,
+    #[serde(rename = "score:write")]
+    ScoreWrite,
+}
+
+impl Scope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Scope::ProfileRead => "profile:read",
+            Scope::ChatRead => "chat:read",
+            Scope::ChatWrite => "chat:write",
+            Scope::ScoreWrite => "score:write",
+        }
+    }
+
+    // 게임에 위임할 수 있는 범위는 게임과 관련된 것으로 제한
+    pub fn is_delegable(&self) -> bool {
+        matches!(self, Scope::ProfileRead | Scope::ScoreWrite)
+    }
+}
+
+impl fmt::Display for Scope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Scope {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "profile:read" => Ok(Scope::ProfileRead),
+            "chat:read" => Ok(Scope::ChatRead),
+            "chat:write" => Ok(Scope::ChatWrite),
+            "score:write" => Ok(Scope::ScoreWrite),
+            _ => Err(()),
+        }
+    }
+}
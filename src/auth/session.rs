@@ -0,0 +1,132 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, TimeZone, Utc};
+use uuid::Uuid;
+use webgame_collection_api_macros::Error;
+
+use super::{
+    get_session_key, get_user_sessions_key,
+    refresh::{revoke_refresh_token_family, revoke_user_refresh_tokens},
+};
+
+pub struct SessionData {
+    pub id: Uuid,
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub last_refreshed_at: DateTime<Utc>,
+}
+
+#[derive(Error)]
+pub enum SessionError {
+    #[error(message = "Redis error")]
+    RedisError(redis::RedisError),
+    #[error(message = "Session not found")]
+    SessionNotFound,
+}
+
+pub async fn list_sessions(
+    user_id: &Uuid,
+    redis_conn: &mut deadpool_redis::Connection,
+) -> Result<Vec<SessionData>, SessionError> {
+    let user_key = get_user_sessions_key(user_id);
+    let session_ids = redis::cmd("SMEMBERS")
+        .arg(&user_key)
+        .query_async::<_, Vec<String>>(redis_conn)
+        .await
+        .map_err(SessionError::RedisError)?
+        .into_iter()
+        .filter_map(|id| Uuid::parse_str(&id).ok())
+        .collect::<Vec<_>>();
+
+    if session_ids.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let mut pipe = redis::pipe();
+    for session_id in &session_ids {
+        pipe.cmd("HGETALL").arg(get_session_key(session_id));
+    }
+    let records = pipe
+        .query_async::<_, Vec<HashMap<String, String>>>(redis_conn)
+        .await
+        .map_err(SessionError::RedisError)?;
+
+    let mut sessions = vec![];
+    let mut expired = vec![];
+    for (id, mut record) in session_ids.into_iter().zip(records) {
+        let timestamp = |record: &HashMap<String, String>, field: &str| {
+            record
+                .get(field)
+                .and_then(|v| v.parse::<i64>().ok())
+                .map(|v| Utc.timestamp(v, 0))
+        };
+
+        match (
+            timestamp(&record, "created_at"),
+            timestamp(&record, "last_refreshed_at"),
+        ) {
+            (Some(created_at), Some(last_refreshed_at)) => sessions.push(SessionData {
+                id,
+                ip: record.remove("ip"),
+                user_agent: record.remove("user_agent"),
+                created_at,
+                last_refreshed_at,
+            }),
+            _ => expired.push(id.to_string()),
+        }
+    }
+
+    if !expired.is_empty() {
+        redis::cmd("SREM")
+            .arg(&user_key)
+            .arg(expired)
+            .query_async::<_, ()>(redis_conn)
+            .await
+            .map_err(SessionError::RedisError)?;
+    }
+
+    sessions.sort_by(|a, b| b.last_refreshed_at.cmp(&a.last_refreshed_at));
+
+    Ok(sessions)
+}
+
+pub async fn revoke_session(
+    user_id: &Uuid,
+    session_id: &Uuid,
+    redis_conn: &mut deadpool_redis::Connection,
+) -> Result<(), SessionError> {
+    let owner_id = redis::cmd("HGET")
+        .arg(get_session_key(session_id))
+        .arg("user_id")
+        .query_async::<_, Option<String>>(redis_conn)
+        .await
+        .map_err(SessionError::RedisError)?;
+
+    if owner_id != Some(user_id.to_string()) {
+        return Err(SessionError::SessionNotFound);
+    }
+
+    revoke_refresh_token_family(user_id, session_id, redis_conn)
+        .await
+        .map_err(SessionError::RedisError)
+}
+
+pub async fn revoke_all_sessions(
+    user_id: &Uuid,
+    redis_conn: &mut deadpool_redis::Connection,
+) -> Result<(), SessionError> {
+    revoke_user_refresh_tokens(user_id, redis_conn)
+        .await
+        .map_err(SessionError::RedisError)
+}
+
+pub async fn session_exists(
+    session_id: &Uuid,
+    redis_conn: &mut deadpool_redis::Connection,
+) -> Result<bool, redis::RedisError> {
+    redis::cmd("EXISTS")
+        .arg(get_session_key(session_id))
+        .query_async(redis_conn)
+        .await
+}
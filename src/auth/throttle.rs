@@ -0,0 +1,93 @@
+use crate::config::CONFIG;
+
+use super::{get_login_failures_key, get_login_lockout_key};
+
+fn subjects(identifier: &str, ip: Option<&str>) -> Vec<(String, i64)> {
+    let mut subjects = vec![(
+        format!("identifier:{}", identifier.to_lowercase()),
+        CONFIG.login_throttle.identifier_threshold,
+    )];
+    if let Some(ip) = ip {
+        subjects.push((format!("ip:{}", ip), CONFIG.login_throttle.ip_threshold));
+    }
+
+    subjects
+}
+
+// 잠금 여부 확인과 시도 횟수 증가를 하나의 스크립트로 처리해 동시에 들어온 시도도 기준치를 넘지 못하게 함
+// 기준치를 넘은 시도는 거부하며, 넘은 횟수만큼 잠금 시간을 두 배씩 늘림
+const REGISTER_ATTEMPT_SCRIPT: &str = r#"
+local window = tonumber(ARGV[1])
+local base = tonumber(ARGV[2])
+local max = tonumber(ARGV[3])
+
+local remaining = 0
+for i = 1, #KEYS, 2 do
+    local ttl = redis.call('TTL', KEYS[i + 1])
+    if ttl > remaining then remaining = ttl end
+end
+if remaining > 0 then return remaining end
+
+for i = 1, #KEYS, 2 do
+    local threshold = tonumber(ARGV[3 + (i + 1) / 2])
+    local attempts = redis.call('INCR', KEYS[i])
+    redis.call('EXPIRE', KEYS[i], window)
+    if attempts > threshold then
+        local lockout = math.floor(math.min(base * 2 ^ math.min(attempts - threshold - 1, 32), max))
+        if lockout > 0 then
+            redis.call('SET', KEYS[i + 1], attempts, 'EX', lockout)
+            if lockout > remaining then remaining = lockout end
+        end
+    end
+end
+return remaining
+"#;
+
+// 로그인 시도를 실패로 먼저 기록하고, 잠긴 상태라면 남은 잠금 시간을 반환
+pub async fn register_login_attempt(
+    identifier: &str,
+    ip: Option<&str>,
+    redis_conn: &mut deadpool_redis::Connection,
+) -> Result<Option<i64>, redis::RedisError> {
+    let config = &CONFIG.login_throttle;
+    let script = redis::Script::new(REGISTER_ATTEMPT_SCRIPT);
+    let mut invocation = script.prepare_invoke();
+    invocation
+        .arg(config.failure_window)
+        .arg(config.base_lockout)
+        .arg(config.max_lockout);
+
+    for (subject, threshold) in subjects(identifier, ip) {
+        invocation
+            .key(get_login_failures_key(&subject))
+            .key(get_login_lockout_key(&subject))
+            .arg(threshold);
+    }
+
+    let remaining = invocation.invoke_async::<_, i64>(redis_conn).await?;
+
+    Ok(Some(remaining).filter(|remaining| *remaining > 0))
+}
+
+// IP 단위의 카운터는 공격자가 자신의 계정으로 로그인해 초기화할 수 없도록 이번 시도만 되돌림
+pub async fn reset_login_failures(
+    identifier: &str,
+    ip: Option<&str>,
+    redis_conn: &mut deadpool_redis::Connection,
+) -> Result<(), redis::RedisError> {
+    let mut subjects = subjects(identifier, ip).into_iter();
+    let mut pipe = redis::pipe();
+
+    if let Some((subject, _)) = subjects.next() {
+        pipe.cmd("DEL")
+            .arg(get_login_failures_key(&subject))
+            .ignore();
+    }
+    if let Some((subject, _)) = subjects.next() {
+        pipe.cmd("DECR")
+            .arg(get_login_failures_key(&subject))
+            .ignore();
+    }
+
+    pipe.query_async(redis_conn).await
+}
@@ -0,0 +1,492 @@
+use std::str::FromStr;
+
+use chrono::Utc;
+use reqwest::Url;
+use ring::{
+    digest, hmac,
+    rand::{SecureRandom, SystemRandom},
+};
+use sqlx::PgPool;
+use uuid::Uuid;
+use webgame_collection_api_macros::Error;
+
+use crate::config::CONFIG;
+
+use super::{create_random_token, get_two_factor_challenge_key, get_two_factor_failures_key};
+
+const TOTP_SECRET_SIZE: usize = 20;
+const TOTP_STEP: i64 = 30;
+const TOTP_DIGITS: u32 = 6;
+const RECOVERY_CODE_COUNT: usize = 10;
+const RECOVERY_CODE_SIZE: usize = 5;
+const MAX_CHALLENGE_ATTEMPTS: i64 = 5;
+
+// challenge가 남아 있을 때만 시도 횟수를 늘려 만료된 키가 TTL 없이 다시 생기지 않게 함
+const USE_CHALLENGE_SCRIPT: &str = r#"
+local user_id = redis.call('HGET', KEYS[1], 'user_id')
+if not user_id then return false end
+if redis.call('HINCRBY', KEYS[1], 'attempts', 1) > tonumber(ARGV[1]) then
+    redis.call('DEL', KEYS[1])
+    return false
+end
+return user_id
+"#;
+
+// 검증 전에 실패 횟수를 먼저 늘려, 동시에 들어온 시도도 기준치를 넘지 못하게 함
+// 잠긴 상태라면 남은 시간을 음수로 반환
+const REGISTER_FAILURE_SCRIPT: &str = r#"
+local failures = tonumber(redis.call('GET', KEYS[1]) or '0')
+if failures >= tonumber(ARGV[1]) then
+    return -math.max(redis.call('TTL', KEYS[1]), 1)
+end
+failures = redis.call('INCR', KEYS[1])
+if failures == 1 then redis.call('EXPIRE', KEYS[1], ARGV[2]) end
+return failures
+"#;
+
+pub struct TotpEnrollment {
+    pub secret: String,
+    pub uri: String,
+}
+
+#[derive(Error)]
+pub enum TwoFactorError {
+    #[error(message = "Database error")]
+    DbError(sqlx::Error),
+    #[error(message = "Redis error")]
+    RedisError(redis::RedisError),
+    #[error(message = "Failed to create a secret")]
+    SecretCreationFailed,
+    #[error(message = "Two-factor authentication is already enabled")]
+    AlreadyEnabled,
+    #[error(message = "Two-factor authentication is not enrolled")]
+    NotEnrolled,
+    #[error(message = "Invalid two-factor authentication code")]
+    InvalidCode,
+    #[error(message = "Invalid or expired two-factor challenge")]
+    InvalidChallenge,
+    #[error(message = "Too many failed two-factor attempts, try again in {0} seconds")]
+    TooManyAttempts(i64),
+}
+
+pub async fn enroll_totp(pool: &PgPool, user_id: &Uuid) -> Result<TotpEnrollment, TwoFactorError> {
+    let mut secret = [0; TOTP_SECRET_SIZE];
+    SystemRandom::new()
+        .fill(&mut secret)
+        .map_err(|_| TwoFactorError::SecretCreationFailed)?;
+    let secret = base32::encode(base32::Alphabet::RFC4648 { padding: false }, &secret);
+
+    // 아직 활성화되지 않은 등록은 새 secret으로 덮어씀
+    let user = sqlx::query!(
+        r#"
+        WITH upserted AS (
+            INSERT INTO public.user_two_factor (user_id, secret)
+            VALUES ($1, $2)
+            ON CONFLICT (user_id) DO UPDATE
+            SET secret = EXCLUDED.secret,
+                recovery_codes = '{}',
+                last_used_step = NULL,
+                created_at = CURRENT_TIMESTAMP
+            WHERE user_two_factor.enabled_at IS NULL
+            RETURNING user_id
+        )
+        SELECT u.email FROM public.user u
+        JOIN upserted ON upserted.user_id = u.id
+        "#,
+        user_id,
+        secret,
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(TwoFactorError::DbError)?
+    .ok_or(TwoFactorError::AlreadyEnabled)?;
+
+    let mut uri = Url::parse("otpauth://totp/").unwrap();
+    uri.path_segments_mut()
+        .unwrap()
+        .pop_if_empty()
+        .push(&format!("{}:{}", CONFIG.totp_issuer, user.email));
+    uri.query_pairs_mut()
+        .append_pair("secret", &secret)
+        .append_pair("issuer", &CONFIG.totp_issuer)
+        .append_pair("algorithm", "SHA1")
+        .append_pair("digits", &TOTP_DIGITS.to_string())
+        .append_pair("period", &TOTP_STEP.to_string());
+
+    Ok(TotpEnrollment {
+        secret,
+        uri: uri.to_string(),
+    })
+}
+
+pub async fn confirm_totp(
+    pool: &PgPool,
+    user_id: &Uuid,
+    code: &str,
+) -> Result<Vec<String>, TwoFactorError> {
+    let record = sqlx::query!(
+        r#"
+        SELECT secret FROM public.user_two_factor
+        WHERE user_id = $1 AND enabled_at IS NULL
+        "#,
+        user_id,
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(TwoFactorError::DbError)?
+    .ok_or(TwoFactorError::NotEnrolled)?;
+
+    let step = match_totp_step(&record.secret, code, None).ok_or(TwoFactorError::InvalidCode)?;
+
+    let recovery_codes = create_recovery_codes().ok_or(TwoFactorError::SecretCreationFailed)?;
+    let hashes = recovery_codes
+        .iter()
+        .map(|code| hash_recovery_code(code))
+        .collect::<Vec<_>>();
+
+    sqlx::query!(
+        r#"
+        UPDATE public.user_two_factor
+        SET enabled_at = CURRENT_TIMESTAMP,
+            recovery_codes = $2,
+            last_used_step = $3
+        WHERE user_id = $1 AND enabled_at IS NULL
+        RETURNING user_id
+        "#,
+        user_id,
+        &hashes,
+        step,
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(TwoFactorError::DbError)?
+    .ok_or(TwoFactorError::NotEnrolled)?;
+
+    Ok(recovery_codes)
+}
+
+pub async fn disable_two_factor(
+    pool: &PgPool,
+    redis_conn: &mut deadpool_redis::Connection,
+    user_id: &Uuid,
+    code: &str,
+) -> Result<(), TwoFactorError> {
+    if !verify_two_factor_code(pool, redis_conn, user_id, code).await? {
+        return Err(TwoFactorError::InvalidCode);
+    }
+
+    sqlx::query!(
+        r#"
+        DELETE FROM public.user_two_factor
+        WHERE user_id = $1
+        "#,
+        user_id,
+    )
+    .execute(pool)
+    .await
+    .map_err(TwoFactorError::DbError)?;
+
+    Ok(())
+}
+
+pub async fn is_two_factor_enabled(pool: &PgPool, user_id: &Uuid) -> Result<bool, sqlx::Error> {
+    sqlx::query!(
+        r#"
+        SELECT user_id FROM public.user_two_factor
+        WHERE user_id = $1 AND enabled_at IS NOT NULL
+        "#,
+        user_id,
+    )
+    .fetch_optional(pool)
+    .await
+    .map(|record| record.is_some())
+}
+
+pub async fn create_two_factor_challenge(
+    redis_conn: &mut deadpool_redis::Connection,
+    user_id: &Uuid,
+) -> Result<String, TwoFactorError> {
+    let token = create_random_token().ok_or(TwoFactorError::SecretCreationFailed)?;
+    let key = get_two_factor_challenge_key(&token);
+
+    redis::pipe()
+        .cmd("HSET")
+        .arg(&key)
+        .arg("user_id")
+        .arg(user_id.to_string())
+        .ignore()
+        .cmd("EXPIRE")
+        .arg(&key)
+        .arg(CONFIG.two_factor_challenge_ttl)
+        .query_async::<_, ()>(redis_conn)
+        .await
+        .map_err(TwoFactorError::RedisError)?;
+
+    Ok(token)
+}
+
+pub async fn verify_two_factor_challenge(
+    pool: &PgPool,
+    redis_conn: &mut deadpool_redis::Connection,
+    token: &str,
+    code: &str,
+) -> Result<Uuid, TwoFactorError> {
+    let key = get_two_factor_challenge_key(token);
+
+    // 6자리 코드를 대입할 수 없도록 시도가 반복되면 challenge 자체를 폐기
+    let user_id = redis::Script::new(USE_CHALLENGE_SCRIPT)
+        .key(&key)
+        .arg(MAX_CHALLENGE_ATTEMPTS)
+        .invoke_async::<_, Option<String>>(redis_conn)
+        .await
+        .map_err(TwoFactorError::RedisError)?
+        .and_then(|user_id| Uuid::from_str(&user_id).ok())
+        .ok_or(TwoFactorError::InvalidChallenge)?;
+
+    if !verify_two_factor_code(pool, redis_conn, &user_id, code).await? {
+        return Err(TwoFactorError::InvalidCode);
+    }
+
+    let deleted = redis::cmd("DEL")
+        .arg(&key)
+        .query_async::<_, i64>(redis_conn)
+        .await
+        .map_err(TwoFactorError::RedisError)?;
+
+    match deleted {
+        0 => Err(TwoFactorError::InvalidChallenge),
+        _ => Ok(user_id),
+    }
+}
+
+// TOTP 코드 또는 복구 코드를 검증하며, 사용된 코드는 다시 사용할 수 없도록 기록
+// challenge를 새로 받아도 코드를 계속 대입할 수 없도록 유저 단위로 실패 횟수를 제한
+async fn verify_two_factor_code(
+    pool: &PgPool,
+    redis_conn: &mut deadpool_redis::Connection,
+    user_id: &Uuid,
+    code: &str,
+) -> Result<bool, TwoFactorError> {
+    let failures_key = get_two_factor_failures_key(user_id);
+    let failures = redis::Script::new(REGISTER_FAILURE_SCRIPT)
+        .key(&failures_key)
+        .arg(CONFIG.two_factor_max_failures)
+        .arg(CONFIG.two_factor_failure_window)
+        .invoke_async::<_, i64>(redis_conn)
+        .await
+        .map_err(TwoFactorError::RedisError)?;
+    if failures < 0 {
+        return Err(TwoFactorError::TooManyAttempts(-failures));
+    }
+
+    let record = sqlx::query!(
+        r#"
+        SELECT secret, last_used_step FROM public.user_two_factor
+        WHERE user_id = $1 AND enabled_at IS NOT NULL
+        "#,
+        user_id,
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(TwoFactorError::DbError)?
+    .ok_or(TwoFactorError::NotEnrolled)?;
+
+    let result = match match_totp_step(&record.secret, code, record.last_used_step) {
+        Some(step) => sqlx::query!(
+            r#"
+            UPDATE public.user_two_factor
+            SET last_used_step = $2
+            WHERE user_id = $1 AND (last_used_step IS NULL OR last_used_step < $2)
+            RETURNING user_id
+            "#,
+            user_id,
+            step,
+        )
+        .fetch_optional(pool)
+        .await
+        .map(|record| record.is_some()),
+        None => sqlx::query!(
+            r#"
+            UPDATE public.user_two_factor
+            SET recovery_codes = array_remove(recovery_codes, $2)
+            WHERE user_id = $1 AND $2 = ANY(recovery_codes)
+            RETURNING user_id
+            "#,
+            user_id,
+            hash_recovery_code(code),
+        )
+        .fetch_optional(pool)
+        .await
+        .map(|record| record.is_some()),
+    };
+
+    let verified = result.map_err(TwoFactorError::DbError)?;
+    if verified {
+        redis::cmd("DEL")
+            .arg(&failures_key)
+            .query_async::<_, ()>(redis_conn)
+            .await
+            .map_err(TwoFactorError::RedisError)?;
+    }
+
+    Ok(verified)
+}
+
+// 시계 오차를 감안해 앞뒤로 한 스텝씩 허용
+fn match_totp_step(secret: &str, code: &str, last_used_step: Option<i64>) -> Option<i64> {
+    if code.len() != TOTP_DIGITS as usize || !code.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let secret = base32::decode(base32::Alphabet::RFC4648 { padding: false }, secret)?;
+    let key = hmac::Key::new(hmac::HMAC_SHA1_FOR_LEGACY_USE_ONLY, &secret);
+    let current_step = Utc::now().timestamp() / TOTP_STEP;
+
+    (current_step - 1..=current_step + 1)
+        .filter(|step| last_used_step.map_or(true, |last| *step > last))
+        .find(|step| {
+            let value = totp_value(&key, *step as u64);
+            format!("{:0width$}", value, width = TOTP_DIGITS as usize) == code
+        })
+}
+
+fn totp_value(key: &hmac::Key, counter: u64) -> u32 {
+    let tag = hmac::sign(key, &counter.to_be_bytes());
+    let hash = tag.as_ref();
+    let offset = (hash[hash.len() - 1] & 0x0f) as usize;
+    let binary = u32::from_be_bytes([
+        hash[offset] & 0x7f,
+        hash[offset + 1],
+        hash[offset + 2],
+        hash[offset + 3],
+    ]);
+
+    binary % 10u32.pow(TOTP_DIGITS)
+}
+
+fn create_recovery_codes() -> Option<Vec<String>> {
+    let rng = SystemRandom::new();
+
+    (0..RECOVERY_CODE_COUNT)
+        .map(|_| {
+            let mut buf = [0; RECOVERY_CODE_SIZE];
+            rng.fill(&mut buf).ok()?;
+            let code =
+                base32::encode(base32::Alphabet::RFC4648 { padding: false }, &buf).to_lowercase();
+
+            Some(format!("{}-{}", &code[..4], &code[4..]))
+        })
+        .collect()
+}
+
+fn hash_recovery_code(code: &str) -> String {
+    let normalized = code.replace('-', "").to_lowercase();
+
+    base64::encode(digest::digest(&digest::SHA256, normalized.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // RFC 4226, RFC 6238의 테스트 벡터에서 사용하는 비밀 값
+    const TEST_SECRET: &[u8] = b"12345678901234567890";
+
+    fn encoded_secret() -> String {
+        base32::encode(base32::Alphabet::RFC4648 { padding: false }, TEST_SECRET)
+    }
+
+    fn code_at(step: i64) -> String {
+        let key = hmac::Key::new(hmac::HMAC_SHA1_FOR_LEGACY_USE_ONLY, TEST_SECRET);
+        format!(
+            "{:0width$}",
+            totp_value(&key, step as u64),
+            width = TOTP_DIGITS as usize
+        )
+    }
+
+    #[test]
+    fn totp_value_matches_test_vectors() {
+        let key = hmac::Key::new(hmac::HMAC_SHA1_FOR_LEGACY_USE_ONLY, TEST_SECRET);
+
+        assert_eq!(totp_value(&key, 0), 755224);
+        assert_eq!(totp_value(&key, 1), 287082);
+        assert_eq!(totp_value(&key, 9), 520489);
+        assert_eq!(totp_value(&key, 1111111109 / TOTP_STEP as u64), 81804);
+        assert_eq!(totp_value(&key, 1234567890 / TOTP_STEP as u64), 5924);
+    }
+
+    #[test]
+    fn accepts_code_within_window() {
+        let current_step = Utc::now().timestamp() / TOTP_STEP;
+
+        for step in current_step - 1..=current_step + 1 {
+            let matched = match_totp_step(&encoded_secret(), &code_at(step), None);
+            assert!(matched.map_or(false, |matched| (matched - step).abs() <= 1));
+        }
+    }
+
+    #[test]
+    fn rejects_code_outside_window() {
+        let current_step = Utc::now().timestamp() / TOTP_STEP;
+
+        assert_eq!(
+            match_totp_step(&encoded_secret(), &code_at(current_step - 10), None),
+            None
+        );
+    }
+
+    #[test]
+    fn rejects_already_used_step() {
+        let current_step = Utc::now().timestamp() / TOTP_STEP;
+
+        assert_eq!(
+            match_totp_step(
+                &encoded_secret(),
+                &code_at(current_step),
+                Some(current_step + 1)
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_code() {
+        assert_eq!(match_totp_step(&encoded_secret(), "12345", None), None);
+        assert_eq!(match_totp_step(&encoded_secret(), "1234567", None), None);
+        assert_eq!(match_totp_step(&encoded_secret(), "12a456", None), None);
+        assert_eq!(match_totp_step("not base32!", "123456", None), None);
+    }
+
+    #[test]
+    fn creates_recovery_codes_in_expected_format() {
+        let codes = create_recovery_codes().unwrap();
+
+        assert_eq!(codes.len(), RECOVERY_CODE_COUNT);
+        for code in &codes {
+            let (head, tail) = code.split_once('-').unwrap();
+            assert_eq!(head.len(), 4);
+            assert_eq!(tail.len(), 4);
+            assert!(code
+                .chars()
+                .all(|c| c == '-' || c.is_ascii_lowercase() || ('2'..='7').contains(&c)));
+        }
+    }
+
+    #[test]
+    fn normalizes_recovery_code_before_hashing() {
+        let code = &create_recovery_codes().unwrap()[0];
+
+        assert_eq!(
+            hash_recovery_code(code),
+            hash_recovery_code(&code.to_uppercase())
+        );
+        assert_eq!(
+            hash_recovery_code(code),
+            hash_recovery_code(&code.replace('-', ""))
+        );
+        assert_ne!(
+            hash_recovery_code("aaaa-aaaa"),
+            hash_recovery_code("aaaa-aaab")
+        );
+    }
+}
@@ -0,0 +1,221 @@
+use std::str::FromStr;
+
+use sqlx::PgPool;
+use uuid::Uuid;
+use webgame_collection_api_macros::Error;
+
+use crate::{
+    auth::AuthMethodType,
+    config::CONFIG,
+    mail::{Mail, MailError, Mailer},
+};
+
+use super::{create_random_token, get_email_verification_token_key, get_verification_resend_key};
+
+#[derive(Error)]
+pub enum VerificationError {
+    #[error(message = "Database error")]
+    DbError(sqlx::Error),
+    #[error(message = "Redis error")]
+    RedisError(redis::RedisError),
+    #[error(message = "Mail sending failed")]
+    MailError(MailError),
+    #[error(message = "Token creation failed")]
+    TokenCreationFailed,
+    #[error(message = "Invalid or expired verification token")]
+    InvalidToken,
+    #[error(message = "Email already taken")]
+    EmailTaken,
+    #[error(message = "Too many verification emails requested, try again in {0} seconds")]
+    RateLimited(i64),
+}
+
+pub async fn send_verification_email(
+    mailer: &dyn Mailer,
+    redis_conn: &mut deadpool_redis::Connection,
+    user_id: &Uuid,
+    email: &str,
+) -> Result<(), VerificationError> {
+    let token = create_random_token().ok_or(VerificationError::TokenCreationFailed)?;
+    let key = get_email_verification_token_key(&token);
+    let value = format!("{}:{}", user_id, email);
+
+    redis::pipe()
+        .cmd("SET")
+        .arg(&[&key, &value])
+        .ignore()
+        .cmd("EXPIRE")
+        .arg(&key)
+        .arg(CONFIG.email_verification_ttl)
+        .query_async::<_, ()>(redis_conn)
+        .await
+        .map_err(VerificationError::RedisError)?;
+
+    mailer
+        .send(Mail {
+            to: email.to_owned(),
+            subject: "Verify your email address".to_owned(),
+            body: format!(
+                "Use the following token to verify your email address: {}",
+                token
+            ),
+        })
+        .await
+        .map_err(VerificationError::MailError)
+}
+
+pub async fn verify_email(
+    pool: &PgPool,
+    redis_conn: &mut deadpool_redis::Connection,
+    token: &str,
+) -> Result<Uuid, VerificationError> {
+    let key = get_email_verification_token_key(token);
+
+    let (value,): (Option<String>,) = redis::pipe()
+        .atomic()
+        .cmd("GET")
+        .arg(&key)
+        .cmd("DEL")
+        .arg(&key)
+        .ignore()
+        .query_async(redis_conn)
+        .await
+        .map_err(VerificationError::RedisError)?;
+
+    let (user_id, email) = value
+        .as_deref()
+        .and_then(|value| value.split_once(':'))
+        .and_then(|(user_id, email)| Some((Uuid::from_str(user_id).ok()?, email)))
+        .ok_or(VerificationError::InvalidToken)?;
+
+    let mut tx = pool.begin().await.map_err(VerificationError::DbError)?;
+
+    // 변경을 기다리는 동안 다른 계정이 같은 주소를 사용하게 된 경우
+    let taken = sqlx::query!(
+        r#"
+        SELECT id FROM public.user
+        WHERE id <> $1 AND email = $2
+        UNION ALL
+        SELECT user_id FROM public.user_auth_method
+        WHERE user_id <> $1 AND type = $3 AND identifier = $2
+        "#,
+        user_id,
+        email,
+        AuthMethodType::Email as AuthMethodType,
+    )
+    .fetch_optional(&mut tx)
+    .await
+    .map_err(VerificationError::DbError)?;
+    if taken.is_some() {
+        return Err(VerificationError::EmailTaken);
+    }
+
+    // 토큰 발급 이후 이메일이 다시 변경된 경우엔 검증하지 않음
+    // 보류 중인 새 주소가 인증되면 로그인 이메일도 이 시점에 바뀜
+    let user = sqlx::query!(
+        r#"
+        UPDATE public.user
+        SET email = $2,
+            pending_email = CASE WHEN pending_email = $2 THEN NULL ELSE pending_email END,
+            email_verified_at = CURRENT_TIMESTAMP
+        WHERE id = $1 AND (email = $2 OR pending_email = $2) AND deleted_at IS NULL
+        RETURNING id
+        "#,
+        user_id,
+        email,
+    )
+    .fetch_optional(&mut tx)
+    .await
+    .map_err(VerificationError::DbError)?
+    .ok_or(VerificationError::InvalidToken)?;
+
+    sqlx::query!(
+        r#"
+        UPDATE public.user_auth_method
+        SET identifier = $3
+        WHERE user_id = $1 AND type = $2
+        "#,
+        user.id,
+        AuthMethodType::Email as AuthMethodType,
+        email,
+    )
+    .execute(&mut tx)
+    .await
+    .map_err(VerificationError::DbError)?;
+
+    tx.commit().await.map_err(VerificationError::DbError)?;
+
+    Ok(user.id)
+}
+
+// 같은 주소로는 일정 시간에 한 번만, 같은 IP에서는 일정 시간 동안 정해진 횟수까지만 다시 보냄
+// 가입되지 않은 주소에 대한 요청도 똑같이 제한해 가입 여부가 드러나지 않도록 함
+async fn check_resend_limit(
+    email: &str,
+    ip: Option<&str>,
+    redis_conn: &mut deadpool_redis::Connection,
+) -> Result<(), VerificationError> {
+    let email_key = get_verification_resend_key(&format!("email:{}", email.to_lowercase()));
+    let ip_key = get_verification_resend_key(&format!("ip:{}", ip.unwrap_or("unknown")));
+
+    let (email_set, email_ttl, ip_count, ip_ttl) = redis::pipe()
+        .atomic()
+        .cmd("SET")
+        .arg(&email_key)
+        .arg(1)
+        .arg("EX")
+        .arg(CONFIG.verification_resend_cooldown)
+        .arg("NX")
+        .cmd("TTL")
+        .arg(&email_key)
+        .cmd("SET")
+        .arg(&ip_key)
+        .arg(0)
+        .arg("EX")
+        .arg(CONFIG.verification_resend_ip_window)
+        .arg("NX")
+        .ignore()
+        .cmd("INCR")
+        .arg(&ip_key)
+        .cmd("TTL")
+        .arg(&ip_key)
+        .query_async::<_, (Option<String>, i64, i64, i64)>(redis_conn)
+        .await
+        .map_err(VerificationError::RedisError)?;
+
+    if email_set.is_none() {
+        return Err(VerificationError::RateLimited(email_ttl.max(0)));
+    }
+    if ip_count > CONFIG.verification_resend_ip_limit {
+        return Err(VerificationError::RateLimited(ip_ttl.max(0)));
+    }
+
+    Ok(())
+}
+
+pub async fn resend_verification_email(
+    pool: &PgPool,
+    mailer: &dyn Mailer,
+    redis_conn: &mut deadpool_redis::Connection,
+    email: &str,
+    ip: Option<&str>,
+) -> Result<(), VerificationError> {
+    check_resend_limit(email, ip, redis_conn).await?;
+
+    let user = sqlx::query!(
+        r#"
+        SELECT id FROM public.user
+        WHERE ((email = $1 AND email_verified_at IS NULL) OR pending_email = $1)
+            AND deleted_at IS NULL
+        "#,
+        email,
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(VerificationError::DbError)?;
+
+    match user {
+        Some(user) => send_verification_email(mailer, redis_conn, &user.id, email).await,
+        None => Ok(()),
+    }
+}
@@ -0,0 +1,171 @@
+use sqlx::PgPool;
+use uuid::Uuid;
+use webgame_collection_api_macros::Error;
+
+use super::ChatData;
+
+#[derive(Error)]
+pub enum ConversationError {
+    #[error(message = "Database error")]
+    DbError(sqlx::Error),
+    #[error(message = "Target user not found")]
+    TargetNotFound,
+    #[error(message = "Cannot send a direct message to yourself")]
+    SelfTarget,
+    #[error(message = "Not a member of the conversation")]
+    NotMember,
+}
+
+// 두 유저 사이의 대화는 하나만 존재하도록 정렬된 ID 쌍을 키로 사용
+fn direct_key(user_a: &Uuid, user_b: &Uuid) -> String {
+    match user_a < user_b {
+        true => format!("{}:{}", user_a, user_b),
+        false => format!("{}:{}", user_b, user_a),
+    }
+}
+
+pub async fn get_or_create_direct_conversation(
+    pool: &PgPool,
+    sender_id: &Uuid,
+    target_id: &Uuid,
+) -> Result<Uuid, ConversationError> {
+    if sender_id == target_id {
+        return Err(ConversationError::SelfTarget);
+    }
+
+    let mut tx = pool.begin().await.map_err(ConversationError::DbError)?;
+
+    sqlx::query!(
+        r#"
+        SELECT id FROM public.user
+        WHERE id = $1 AND deleted_at IS NULL
+        "#,
+        target_id,
+    )
+    .fetch_optional(&mut tx)
+    .await
+    .map_err(ConversationError::DbError)?
+    .ok_or(ConversationError::TargetNotFound)?;
+
+    // 동시에 생성되는 경우에도 기존 대화를 반환하도록 충돌 시 갱신
+    let conversation = sqlx::query!(
+        r#"
+        INSERT INTO public.conversation (id, direct_key)
+        VALUES ($1, $2)
+        ON CONFLICT (direct_key) DO UPDATE SET direct_key = EXCLUDED.direct_key
+        RETURNING id
+        "#,
+        Uuid::new_v4(),
+        direct_key(sender_id, target_id),
+    )
+    .fetch_one(&mut tx)
+    .await
+    .map_err(ConversationError::DbError)?;
+
+    sqlx::query!(
+        r#"
+        INSERT INTO public.conversation_member (conversation_id, user_id)
+        VALUES ($1, $2), ($1, $3)
+        ON CONFLICT DO NOTHING
+        "#,
+        conversation.id,
+        sender_id,
+        target_id,
+    )
+    .execute(&mut tx)
+    .await
+    .map_err(ConversationError::DbError)?;
+
+    tx.commit().await.map_err(ConversationError::DbError)?;
+
+    Ok(conversation.id)
+}
+
+pub async fn is_member(
+    pool: &PgPool,
+    conversation_id: &Uuid,
+    user_id: &Uuid,
+) -> Result<bool, ConversationError> {
+    sqlx::query!(
+        r#"
+        SELECT user_id FROM public.conversation_member
+        WHERE conversation_id = $1 AND user_id = $2
+        "#,
+        conversation_id,
+        user_id,
+    )
+    .fetch_optional(pool)
+    .await
+    .map(|member| member.is_some())
+    .map_err(ConversationError::DbError)
+}
+
+// 메시지를 저장하고, 발신자를 제외한 대화 참여자들을 대상으로 하는 ChatData를 반환
+pub async fn store_message(
+    pool: &PgPool,
+    conversation_id: &Uuid,
+    sender_id: &Uuid,
+    message: String,
+) -> Result<ChatData, ConversationError> {
+    let mut tx = pool.begin().await.map_err(ConversationError::DbError)?;
+
+    let target_ids = sqlx::query!(
+        r#"
+        SELECT user_id FROM public.conversation_member
+        WHERE conversation_id = $1
+        "#,
+        conversation_id,
+    )
+    .fetch_all(&mut tx)
+    .await
+    .map_err(ConversationError::DbError)?
+    .into_iter()
+    .map(|member| member.user_id)
+    .collect::<Vec<_>>();
+
+    if !target_ids.contains(sender_id) {
+        return Err(ConversationError::NotMember);
+    }
+
+    let record = sqlx::query!(
+        r#"
+        INSERT INTO public.chat_message (id, conversation_id, sender_id, message)
+        VALUES ($1, $2, $3, $4)
+        RETURNING id, created_at
+        "#,
+        Uuid::new_v4(),
+        conversation_id,
+        sender_id,
+        message,
+    )
+    .fetch_one(&mut tx)
+    .await
+    .map_err(ConversationError::DbError)?;
+
+    sqlx::query!(
+        r#"
+        UPDATE public.conversation
+        SET last_message_at = $2
+        WHERE id = $1
+        "#,
+        conversation_id,
+        record.created_at,
+    )
+    .execute(&mut tx)
+    .await
+    .map_err(ConversationError::DbError)?;
+
+    tx.commit().await.map_err(ConversationError::DbError)?;
+
+    Ok(ChatData {
+        id: record.id,
+        conversation_id: *conversation_id,
+        sender_id: *sender_id,
+        target_ids: target_ids
+            .into_iter()
+            .filter(|user_id| user_id != sender_id)
+            .collect(),
+        message,
+        created_at: record.created_at,
+    })
+}
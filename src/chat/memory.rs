@@ -0,0 +1,81 @@
+use async_trait::async_trait;
+use tokio::sync::{mpsc, Mutex};
+
+use super::{deliver, ChatData, ChatError, ChatTransport};
+
+// 단일 인스턴스 내에서만 전달
+pub struct MemoryTransport {
+    tx: mpsc::Sender<ChatData>,
+    rx: Mutex<mpsc::Receiver<ChatData>>,
+}
+
+impl MemoryTransport {
+    pub fn new() -> MemoryTransport {
+        let (tx, rx) = mpsc::channel(64);
+
+        MemoryTransport {
+            tx,
+            rx: Mutex::new(rx),
+        }
+    }
+}
+
+#[async_trait]
+impl ChatTransport for MemoryTransport {
+    async fn send(&self, chat: ChatData) -> Result<(), ChatError> {
+        self.tx
+            .send(chat)
+            .await
+            .map_err(|e| ChatError::SendFailed(anyhow::Error::new(e)))
+    }
+
+    async fn run(&self) {
+        let mut rx = self.rx.lock().await;
+        while let Some(chat) = rx.recv().await {
+            deliver(&chat);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, time::Duration};
+
+    use chrono::Utc;
+    use futures::StreamExt;
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::chat::subscribe;
+
+    #[tokio::test]
+    async fn delivers_sent_chat_to_subscribers() {
+        let transport = Arc::new(MemoryTransport::new());
+        let runner = tokio::spawn({
+            let transport = transport.clone();
+            async move { transport.run().await }
+        });
+
+        let target_id = Uuid::new_v4();
+        let mut subscription = subscribe(target_id);
+        let sent = transport
+            .send(ChatData {
+                id: Uuid::new_v4(),
+                conversation_id: Uuid::new_v4(),
+                sender_id: Uuid::new_v4(),
+                target_ids: vec![target_id],
+                message: "hello".to_owned(),
+                created_at: Utc::now(),
+            })
+            .await;
+        assert!(sent.is_ok());
+
+        let chat = tokio::time::timeout(Duration::from_secs(1), subscription.next())
+            .await
+            .expect("Chat not delivered in time")
+            .expect("Subscription closed");
+        assert_eq!(chat.message, "hello");
+
+        runner.abort();
+    }
+}
@@ -0,0 +1,171 @@
+pub mod conversation;
+pub mod memory;
+pub mod redis_pubsub;
+pub mod room;
+#[cfg(feature = "sqs")]
+pub mod sqs;
+
+use std::{
+    collections::HashMap,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use futures::Stream;
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc::{self, error::TrySendError};
+use uuid::Uuid;
+use webgame_collection_api_macros::Error;
+
+use crate::{
+    config::{ChatBackendConfig, CONFIG},
+    schema::types::{
+        chat::Chat,
+        node::{IdData, NodeIdent},
+        scalars::DateTimeScalar,
+    },
+};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ChatData {
+    pub id: Uuid,
+    pub conversation_id: Uuid,
+    pub sender_id: Uuid,
+    pub target_ids: Vec<Uuid>,
+    pub message: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl ChatData {
+    pub fn to_chat(&self) -> Chat {
+        Chat {
+            id: IdData {
+                ty: NodeIdent::Chat,
+                uuid: self.id,
+            }
+            .to_id_scalar(),
+            conversation_id: IdData {
+                ty: NodeIdent::Conversation,
+                uuid: self.conversation_id,
+            }
+            .to_id_scalar(),
+            sender_id: IdData {
+                ty: NodeIdent::User,
+                uuid: self.sender_id,
+            }
+            .to_id_scalar(),
+            message: self.message.clone(),
+            created_at: DateTimeScalar(self.created_at),
+            conversation_uuid: self.conversation_id,
+        }
+    }
+}
+
+#[derive(Error)]
+pub enum ChatError {
+    #[error(message = "Failed to serialize the chat")]
+    SerializeError(serde_json::Error),
+    #[error(message = "Failed to send the chat")]
+    SendFailed(anyhow::Error),
+}
+
+// 메시지를 보내는 쪽과, 전달된 메시지를 현재 인스턴스의 구독자에게 넘겨주는 쪽을 함께 구현
+#[async_trait]
+pub trait ChatTransport: Send + Sync {
+    async fn send(&self, chat: ChatData) -> Result<(), ChatError>;
+    async fn run(&self);
+}
+
+pub type ChatTransportRef = Arc<dyn ChatTransport>;
+
+pub fn build_chat_transport(redis_pool: deadpool_redis::Pool) -> ChatTransportRef {
+    match &CONFIG.chat_backend {
+        ChatBackendConfig::Memory => Arc::new(memory::MemoryTransport::new()),
+        ChatBackendConfig::Redis => Arc::new(redis_pubsub::RedisTransport::new(redis_pool)),
+        #[cfg(feature = "sqs")]
+        ChatBackendConfig::Sqs {
+            endpoint,
+            region,
+            queue_url,
+        } => Arc::new(sqs::SqsTransport::new(endpoint, region, queue_url)),
+    }
+}
+
+// 한 유저가 여러 탭이나 기기에서 동시에 구독할 수 있도록 구독마다 connection id를 부여
+lazy_static::lazy_static! {
+    pub static ref CHANNEL_MAP: Mutex<HashMap<Uuid, HashMap<Uuid, mpsc::Sender<Chat>>>> = Mutex::new(HashMap::new());
+}
+
+// 구독 스트림이 drop되면 CHANNEL_MAP에서도 제거됨
+pub struct ChatSubscription {
+    user_id: Uuid,
+    connection_id: Uuid,
+    rx: mpsc::Receiver<Chat>,
+}
+
+impl Stream for ChatSubscription {
+    type Item = Chat;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Chat>> {
+        self.get_mut().rx.poll_recv(cx)
+    }
+}
+
+impl Drop for ChatSubscription {
+    fn drop(&mut self) {
+        let mut map_guard = CHANNEL_MAP.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(connections) = map_guard.get_mut(&self.user_id) {
+            connections.remove(&self.connection_id);
+            if connections.is_empty() {
+                map_guard.remove(&self.user_id);
+            }
+        }
+    }
+}
+
+pub fn subscribe(user_id: Uuid) -> ChatSubscription {
+    let connection_id = Uuid::new_v4();
+    let (tx, rx) = mpsc::channel::<Chat>(10);
+
+    let mut map_guard = CHANNEL_MAP.lock().unwrap_or_else(|e| e.into_inner());
+    map_guard
+        .entry(user_id)
+        .or_default()
+        .insert(connection_id, tx);
+
+    ChatSubscription {
+        user_id,
+        connection_id,
+        rx,
+    }
+}
+
+// 현재 인스턴스에 연결된 구독자들에게 전달
+// 버퍼가 가득 찬 구독자 하나가 전체 전달을 막지 않도록 기다리지 않고, 해당 구독에는 이 메시지를 건너뜀
+pub fn deliver(chat: &ChatData) {
+    let mut map_guard = CHANNEL_MAP.lock().unwrap_or_else(|e| e.into_inner());
+    for user_id in &chat.target_ids {
+        if let Some(connections) = map_guard.get_mut(user_id) {
+            connections.retain(|connection_id, tx| match tx.try_send(chat.to_chat()) {
+                Ok(_) => true,
+                Err(TrySendError::Full(_)) => {
+                    log::warn!(
+                        "Dropping the chat {} for the subscription {} of {} with a full buffer",
+                        chat.id,
+                        connection_id,
+                        user_id
+                    );
+                    true
+                }
+                Err(TrySendError::Closed(_)) => false,
+            });
+
+            if connections.is_empty() {
+                map_guard.remove(user_id);
+            }
+        }
+    }
+}
@@ -0,0 +1,66 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use futures::StreamExt;
+
+use super::{deliver, ChatData, ChatError, ChatTransport};
+
+static CHAT_REDIS_CHANNEL: &str = "chat/messages";
+const RESUBSCRIBE_DELAY: Duration = Duration::from_secs(1);
+
+// 메시지를 Redis에 발행하고, 각 인스턴스는 구독한 메시지를 자신의 구독자에게 전달
+pub struct RedisTransport {
+    redis_pool: deadpool_redis::Pool,
+}
+
+impl RedisTransport {
+    pub fn new(redis_pool: deadpool_redis::Pool) -> RedisTransport {
+        RedisTransport { redis_pool }
+    }
+
+    async fn receive(&self) -> anyhow::Result<()> {
+        // pub/sub 모드의 연결은 다른 명령에 사용할 수 없으므로 풀에서 분리
+        let redis_conn = deadpool_redis::Connection::take(self.redis_pool.get().await?);
+        let mut pubsub = redis_conn.into_pubsub();
+        pubsub.subscribe(CHAT_REDIS_CHANNEL).await?;
+
+        let mut messages = pubsub.on_message();
+        while let Some(message) = messages.next().await {
+            let payload = message.get_payload::<String>()?;
+            match serde_json::from_str::<ChatData>(&payload) {
+                Ok(chat) => deliver(&chat),
+                Err(e) => log::warn!("Received an invalid chat from Redis: {}", e),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ChatTransport for RedisTransport {
+    async fn send(&self, chat: ChatData) -> Result<(), ChatError> {
+        let payload = serde_json::to_string(&chat).map_err(ChatError::SerializeError)?;
+        let mut redis_conn = self
+            .redis_pool
+            .get()
+            .await
+            .map_err(|e| ChatError::SendFailed(anyhow::Error::new(e)))?;
+
+        redis::cmd("PUBLISH")
+            .arg(CHAT_REDIS_CHANNEL)
+            .arg(payload)
+            .query_async::<_, ()>(&mut redis_conn)
+            .await
+            .map_err(|e| ChatError::SendFailed(anyhow::Error::new(e)))
+    }
+
+    async fn run(&self) {
+        loop {
+            if let Err(e) = self.receive().await {
+                log::error!("Chat subscription to Redis failed: {}", e);
+            }
+            tokio::time::sleep(RESUBSCRIBE_DELAY).await;
+        }
+    }
+}
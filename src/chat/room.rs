@@ -0,0 +1,491 @@
+use async_graphql::Enum;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+use webgame_collection_api_macros::Error;
+
+const MAX_ROOM_NAME_LENGTH: usize = 100;
+
+#[derive(sqlx::Type, Enum, Copy, Clone, Eq, PartialEq, Debug)]
+#[sqlx(type_name = "conversation_type", rename_all = "lowercase")]
+pub enum ConversationType {
+    Direct,
+    Room,
+}
+
+#[derive(sqlx::Type, Enum, Copy, Clone, Eq, PartialEq, Debug)]
+#[sqlx(type_name = "room_member_role", rename_all = "lowercase")]
+pub enum RoomMemberRole {
+    Owner,
+    Moderator,
+    Member,
+}
+
+pub struct RoomData {
+    pub id: Uuid,
+    pub name: Option<String>,
+    pub is_public: bool,
+    pub game_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+pub struct RoomMemberData {
+    pub user_id: Uuid,
+    pub role: RoomMemberRole,
+    pub joined_at: DateTime<Utc>,
+}
+
+#[derive(Error)]
+pub enum RoomError {
+    #[error(message = "Database error")]
+    DbError(sqlx::Error),
+    #[error(message = "Room not found")]
+    RoomNotFound,
+    #[error(message = "User not found")]
+    UserNotFound,
+    #[error(message = "Invalid room name")]
+    InvalidName,
+    #[error(message = "Not a member of the room")]
+    NotMember,
+    #[error(message = "Already a member of the room")]
+    AlreadyMember,
+    #[error(message = "The room is private and requires an invitation")]
+    NotInvited,
+    #[error(message = "Banned from the room")]
+    Banned,
+    #[error(message = "Insufficient permission in the room")]
+    Forbidden,
+    #[error(message = "The owner role cannot be assigned")]
+    InvalidRole,
+}
+
+pub async fn create_room(
+    pool: &PgPool,
+    owner_id: &Uuid,
+    name: String,
+    is_public: bool,
+) -> Result<RoomData, RoomError> {
+    let name = name.trim().to_owned();
+    if name.is_empty() || name.chars().count() > MAX_ROOM_NAME_LENGTH {
+        return Err(RoomError::InvalidName);
+    }
+
+    let mut tx = pool.begin().await.map_err(RoomError::DbError)?;
+
+    let room = sqlx::query!(
+        r#"
+        INSERT INTO public.conversation (id, type, name, is_public)
+        VALUES ($1, 'room', $2, $3)
+        RETURNING id, name, is_public, game_id, created_at
+        "#,
+        Uuid::new_v4(),
+        name,
+        is_public,
+    )
+    .fetch_one(&mut tx)
+    .await
+    .map_err(RoomError::DbError)?;
+
+    sqlx::query!(
+        r#"
+        INSERT INTO public.conversation_member (conversation_id, user_id, role)
+        VALUES ($1, $2, 'owner')
+        "#,
+        room.id,
+        owner_id,
+    )
+    .execute(&mut tx)
+    .await
+    .map_err(RoomError::DbError)?;
+
+    tx.commit().await.map_err(RoomError::DbError)?;
+
+    Ok(RoomData {
+        id: room.id,
+        name: room.name,
+        is_public: room.is_public,
+        game_id: room.game_id,
+        created_at: room.created_at,
+    })
+}
+
+// 게임마다 하나의 공개 로비가 존재하며, 게임이 추가될 때 DB에서 생성됨
+pub async fn get_lobby(pool: &PgPool, game_id: &Uuid) -> Result<RoomData, RoomError> {
+    let room = sqlx::query!(
+        r#"
+        SELECT id, name, is_public, game_id, created_at FROM public.conversation
+        WHERE game_id = $1 AND type = 'room'
+        "#,
+        game_id,
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(RoomError::DbError)?
+    .ok_or(RoomError::RoomNotFound)?;
+
+    Ok(RoomData {
+        id: room.id,
+        name: room.name,
+        is_public: room.is_public,
+        game_id: room.game_id,
+        created_at: room.created_at,
+    })
+}
+
+pub async fn get_room(pool: &PgPool, room_id: &Uuid) -> Result<RoomData, RoomError> {
+    let room = sqlx::query!(
+        r#"
+        SELECT id, name, is_public, game_id, created_at FROM public.conversation
+        WHERE id = $1 AND type = 'room'
+        "#,
+        room_id,
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(RoomError::DbError)?
+    .ok_or(RoomError::RoomNotFound)?;
+
+    Ok(RoomData {
+        id: room.id,
+        name: room.name,
+        is_public: room.is_public,
+        game_id: room.game_id,
+        created_at: room.created_at,
+    })
+}
+
+pub async fn get_room_members(
+    pool: &PgPool,
+    room_id: &Uuid,
+) -> Result<Vec<RoomMemberData>, RoomError> {
+    sqlx::query!(
+        r#"
+        SELECT user_id, role AS "role: RoomMemberRole", joined_at
+        FROM public.conversation_member
+        WHERE conversation_id = $1
+        ORDER BY joined_at ASC
+        "#,
+        room_id,
+    )
+    .fetch_all(pool)
+    .await
+    .map(|members| {
+        members
+            .into_iter()
+            .map(|member| RoomMemberData {
+                user_id: member.user_id,
+                role: member.role,
+                joined_at: member.joined_at,
+            })
+            .collect()
+    })
+    .map_err(RoomError::DbError)
+}
+
+async fn get_member_role(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    room_id: &Uuid,
+    user_id: &Uuid,
+) -> Result<Option<RoomMemberRole>, RoomError> {
+    sqlx::query!(
+        r#"
+        SELECT role AS "role: RoomMemberRole" FROM public.conversation_member
+        WHERE conversation_id = $1 AND user_id = $2
+        "#,
+        room_id,
+        user_id,
+    )
+    .fetch_optional(tx)
+    .await
+    .map(|member| member.map(|member| member.role))
+    .map_err(RoomError::DbError)
+}
+
+pub async fn join_room(pool: &PgPool, room_id: &Uuid, user_id: &Uuid) -> Result<(), RoomError> {
+    let mut tx = pool.begin().await.map_err(RoomError::DbError)?;
+
+    let room = sqlx::query!(
+        r#"
+        SELECT is_public FROM public.conversation
+        WHERE id = $1 AND type = 'room'
+        FOR UPDATE
+        "#,
+        room_id,
+    )
+    .fetch_optional(&mut tx)
+    .await
+    .map_err(RoomError::DbError)?
+    .ok_or(RoomError::RoomNotFound)?;
+
+    if get_member_role(&mut tx, room_id, user_id).await?.is_some() {
+        return Err(RoomError::AlreadyMember);
+    }
+
+    let banned = sqlx::query!(
+        r#"
+        SELECT user_id FROM public.conversation_ban
+        WHERE conversation_id = $1 AND user_id = $2
+        "#,
+        room_id,
+        user_id,
+    )
+    .fetch_optional(&mut tx)
+    .await
+    .map_err(RoomError::DbError)?
+    .is_some();
+    if banned {
+        return Err(RoomError::Banned);
+    }
+
+    // 비공개 방은 초대를 받은 경우에만 참여할 수 있으며, 초대는 참여와 함께 소모됨
+    let invited = sqlx::query!(
+        r#"
+        DELETE FROM public.conversation_invite
+        WHERE conversation_id = $1 AND user_id = $2
+        RETURNING user_id
+        "#,
+        room_id,
+        user_id,
+    )
+    .fetch_optional(&mut tx)
+    .await
+    .map_err(RoomError::DbError)?
+    .is_some();
+
+    if !room.is_public && !invited {
+        return Err(RoomError::NotInvited);
+    }
+
+    sqlx::query!(
+        r#"
+        INSERT INTO public.conversation_member (conversation_id, user_id)
+        VALUES ($1, $2)
+        "#,
+        room_id,
+        user_id,
+    )
+    .execute(&mut tx)
+    .await
+    .map_err(RoomError::DbError)?;
+
+    tx.commit().await.map_err(RoomError::DbError)
+}
+
+pub async fn leave_room(pool: &PgPool, room_id: &Uuid, user_id: &Uuid) -> Result<(), RoomError> {
+    let mut tx = pool.begin().await.map_err(RoomError::DbError)?;
+
+    let role = sqlx::query!(
+        r#"
+        DELETE FROM public.conversation_member m
+        USING public.conversation c
+        WHERE m.conversation_id = $1 AND m.user_id = $2
+            AND c.id = m.conversation_id AND c.type = 'room'
+        RETURNING m.role AS "role: RoomMemberRole"
+        "#,
+        room_id,
+        user_id,
+    )
+    .fetch_optional(&mut tx)
+    .await
+    .map_err(RoomError::DbError)?
+    .ok_or(RoomError::NotMember)?
+    .role;
+
+    // 방장이 나가면 모더레이터, 가장 먼저 참여한 멤버 순으로 방장을 넘김
+    if role == RoomMemberRole::Owner {
+        sqlx::query!(
+            r#"
+            UPDATE public.conversation_member
+            SET role = 'owner'
+            WHERE conversation_id = $1 AND user_id = (
+                SELECT user_id FROM public.conversation_member
+                WHERE conversation_id = $1
+                ORDER BY (role = 'moderator') DESC, joined_at ASC
+                LIMIT 1
+            )
+            "#,
+            room_id,
+        )
+        .execute(&mut tx)
+        .await
+        .map_err(RoomError::DbError)?;
+    }
+
+    tx.commit().await.map_err(RoomError::DbError)
+}
+
+pub async fn invite_to_room(
+    pool: &PgPool,
+    room_id: &Uuid,
+    inviter_id: &Uuid,
+    user_id: &Uuid,
+) -> Result<(), RoomError> {
+    let mut tx = pool.begin().await.map_err(RoomError::DbError)?;
+
+    let inviter_role = match get_member_role(&mut tx, room_id, inviter_id).await? {
+        Some(RoomMemberRole::Member) => return Err(RoomError::Forbidden),
+        Some(role) => role,
+        None => return Err(RoomError::NotMember),
+    };
+    if get_member_role(&mut tx, room_id, user_id).await?.is_some() {
+        return Err(RoomError::AlreadyMember);
+    }
+
+    sqlx::query!(
+        r#"
+        SELECT id FROM public.user
+        WHERE id = $1 AND deleted_at IS NULL
+        "#,
+        user_id,
+    )
+    .fetch_optional(&mut tx)
+    .await
+    .map_err(RoomError::DbError)?
+    .ok_or(RoomError::UserNotFound)?;
+
+    // 차단된 유저는 방장이나 직접 차단한 멤버가 초대할 때만 차단이 해제됨
+    let ban = sqlx::query!(
+        r#"
+        DELETE FROM public.conversation_ban
+        WHERE conversation_id = $1 AND user_id = $2
+        RETURNING banned_by
+        "#,
+        room_id,
+        user_id,
+    )
+    .fetch_optional(&mut tx)
+    .await
+    .map_err(RoomError::DbError)?;
+
+    if let Some(ban) = ban {
+        if inviter_role != RoomMemberRole::Owner && &ban.banned_by != inviter_id {
+            return Err(RoomError::Banned);
+        }
+    }
+
+    sqlx::query!(
+        r#"
+        INSERT INTO public.conversation_invite (conversation_id, user_id, invited_by)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (conversation_id, user_id) DO UPDATE
+        SET invited_by = EXCLUDED.invited_by, created_at = CURRENT_TIMESTAMP
+        "#,
+        room_id,
+        user_id,
+        inviter_id,
+    )
+    .execute(&mut tx)
+    .await
+    .map_err(RoomError::DbError)?;
+
+    tx.commit().await.map_err(RoomError::DbError)
+}
+
+// 방장은 모든 멤버를, 모더레이터는 일반 멤버만 내보낼 수 있음
+// 방장이 없는 게임 로비는 사이트 모더레이터가 관리
+pub async fn kick_from_room(
+    pool: &PgPool,
+    room_id: &Uuid,
+    actor_id: &Uuid,
+    user_id: &Uuid,
+    site_moderator: bool,
+) -> Result<(), RoomError> {
+    let mut tx = pool.begin().await.map_err(RoomError::DbError)?;
+
+    // DM 대화는 방이 아니므로 내보낼 수 없음
+    sqlx::query!(
+        r#"
+        SELECT id FROM public.conversation
+        WHERE id = $1 AND type = 'room'
+        "#,
+        room_id,
+    )
+    .fetch_optional(&mut tx)
+    .await
+    .map_err(RoomError::DbError)?
+    .ok_or(RoomError::RoomNotFound)?;
+
+    let actor_role = get_member_role(&mut tx, room_id, actor_id).await?;
+    let target_role = get_member_role(&mut tx, room_id, user_id)
+        .await?
+        .ok_or(RoomError::NotMember)?;
+
+    let allowed = match (actor_role, target_role) {
+        _ if actor_id == user_id => false,
+        _ if site_moderator => true,
+        (Some(RoomMemberRole::Owner), _) => true,
+        (Some(RoomMemberRole::Moderator), RoomMemberRole::Member) => true,
+        _ => false,
+    };
+    if !allowed {
+        return Err(RoomError::Forbidden);
+    }
+
+    sqlx::query!(
+        r#"
+        DELETE FROM public.conversation_member
+        WHERE conversation_id = $1 AND user_id = $2
+        "#,
+        room_id,
+        user_id,
+    )
+    .execute(&mut tx)
+    .await
+    .map_err(RoomError::DbError)?;
+
+    // 내보낸 유저는 다시 초대받기 전까지 참여할 수 없음
+    sqlx::query!(
+        r#"
+        INSERT INTO public.conversation_ban (conversation_id, user_id, banned_by)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (conversation_id, user_id) DO UPDATE
+        SET banned_by = EXCLUDED.banned_by, created_at = CURRENT_TIMESTAMP
+        "#,
+        room_id,
+        user_id,
+        actor_id,
+    )
+    .execute(&mut tx)
+    .await
+    .map_err(RoomError::DbError)?;
+
+    tx.commit().await.map_err(RoomError::DbError)
+}
+
+pub async fn set_room_member_role(
+    pool: &PgPool,
+    room_id: &Uuid,
+    owner_id: &Uuid,
+    user_id: &Uuid,
+    role: RoomMemberRole,
+) -> Result<(), RoomError> {
+    if role == RoomMemberRole::Owner {
+        return Err(RoomError::InvalidRole);
+    }
+
+    let mut tx = pool.begin().await.map_err(RoomError::DbError)?;
+
+    match get_member_role(&mut tx, room_id, owner_id).await? {
+        Some(RoomMemberRole::Owner) if owner_id != user_id => {}
+        Some(_) => return Err(RoomError::Forbidden),
+        None => return Err(RoomError::NotMember),
+    }
+
+    sqlx::query!(
+        r#"
+        UPDATE public.conversation_member
+        SET role = $3
+        WHERE conversation_id = $1 AND user_id = $2
+        RETURNING user_id
+        "#,
+        room_id,
+        user_id,
+        role as RoomMemberRole,
+    )
+    .fetch_optional(&mut tx)
+    .await
+    .map_err(RoomError::DbError)?
+    .ok_or(RoomError::NotMember)?;
+
+    tx.commit().await.map_err(RoomError::DbError)
+}
@@ -0,0 +1,161 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use rusoto_core::Region;
+use rusoto_sqs::{DeleteMessageRequest, ReceiveMessageRequest, SendMessageRequest, Sqs, SqsClient};
+
+use super::{deliver, ChatData, ChatError, ChatTransport};
+
+const RECEIVE_WAIT_TIME_SECONDS: i64 = 20;
+const RECEIVE_MAX_MESSAGES: i64 = 10;
+const RETRY_DELAY: Duration = Duration::from_secs(1);
+
+// SQS 호환 큐(ElasticMQ 등)를 통해 전달
+// 큐의 메시지는 하나의 소비자에게만 전달되므로 단일 인스턴스에서만 사용 가능
+pub struct SqsTransport {
+    client: SqsClient,
+    queue_url: String,
+}
+
+impl SqsTransport {
+    pub fn new(endpoint: &str, region: &str, queue_url: &str) -> SqsTransport {
+        let region = Region::Custom {
+            name: region.to_owned(),
+            endpoint: endpoint.to_owned(),
+        };
+
+        SqsTransport {
+            client: SqsClient::new(region),
+            queue_url: queue_url.to_owned(),
+        }
+    }
+
+    async fn receive(&self) -> anyhow::Result<()> {
+        let result = self
+            .client
+            .receive_message(ReceiveMessageRequest {
+                queue_url: self.queue_url.clone(),
+                max_number_of_messages: Some(RECEIVE_MAX_MESSAGES),
+                wait_time_seconds: Some(RECEIVE_WAIT_TIME_SECONDS),
+                ..Default::default()
+            })
+            .await?;
+
+        for message in result.messages.unwrap_or_default() {
+            match message
+                .body
+                .as_deref()
+                .map(serde_json::from_str::<ChatData>)
+            {
+                Some(Ok(chat)) => deliver(&chat),
+                Some(Err(e)) => log::warn!("Received an invalid chat from SQS: {}", e),
+                None => log::warn!("Received an empty chat from SQS"),
+            }
+
+            // 잘못된 메시지도 반복해서 수신되지 않도록 삭제
+            // 삭제에 실패해도 이미 전달된 나머지 메시지가 다시 전달되지 않도록 계속 진행
+            if let Some(receipt_handle) = message.receipt_handle {
+                let result = self
+                    .client
+                    .delete_message(DeleteMessageRequest {
+                        queue_url: self.queue_url.clone(),
+                        receipt_handle,
+                    })
+                    .await;
+                if let Err(e) = result {
+                    log::warn!("Failed to delete a chat from SQS: {}", e);
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ChatTransport for SqsTransport {
+    async fn send(&self, chat: ChatData) -> Result<(), ChatError> {
+        let message_body = serde_json::to_string(&chat).map_err(ChatError::SerializeError)?;
+
+        self.client
+            .send_message(SendMessageRequest {
+                queue_url: self.queue_url.clone(),
+                message_body,
+                ..Default::default()
+            })
+            .await
+            .map(|_| ())
+            .map_err(|e| ChatError::SendFailed(anyhow::Error::new(e)))
+    }
+
+    async fn run(&self) {
+        loop {
+            if let Err(e) = self.receive().await {
+                log::error!("Failed to receive chats from SQS: {}", e);
+                tokio::time::sleep(RETRY_DELAY).await;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, sync::Arc};
+
+    use chrono::Utc;
+    use futures::StreamExt;
+    use rusoto_sqs::CreateQueueRequest;
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::chat::subscribe;
+
+    // ElasticMQ가 필요하므로 `cargo test --features sqs -- --ignored`로 실행
+    #[tokio::test]
+    #[ignore]
+    async fn delivers_sent_chat_through_elasticmq() {
+        let endpoint =
+            env::var("ELASTICMQ_ENDPOINT").unwrap_or_else(|_| "http://localhost:9324".to_owned());
+        let region = Region::Custom {
+            name: "elasticmq".to_owned(),
+            endpoint: endpoint.clone(),
+        };
+        let queue_url = SqsClient::new(region)
+            .create_queue(CreateQueueRequest {
+                queue_name: format!("chat-test-{}", Uuid::new_v4()),
+                ..Default::default()
+            })
+            .await
+            .expect("Failed to create a queue")
+            .queue_url
+            .expect("Queue URL not returned");
+
+        let transport = Arc::new(SqsTransport::new(&endpoint, "elasticmq", &queue_url));
+        let runner = tokio::spawn({
+            let transport = transport.clone();
+            async move { transport.run().await }
+        });
+
+        let target_id = Uuid::new_v4();
+        let mut subscription = subscribe(target_id);
+        let sent = transport
+            .send(ChatData {
+                id: Uuid::new_v4(),
+                conversation_id: Uuid::new_v4(),
+                sender_id: Uuid::new_v4(),
+                target_ids: vec![target_id],
+                message: "hello".to_owned(),
+                created_at: Utc::now(),
+            })
+            .await;
+        assert!(sent.is_ok());
+
+        let chat = tokio::time::timeout(Duration::from_secs(30), subscription.next())
+            .await
+            .expect("Chat not delivered in time")
+            .expect("Subscription closed");
+        assert_eq!(chat.message, "hello");
+
+        runner.abort();
+    }
+}
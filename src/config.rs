@@ -1,17 +1,170 @@
-use std::{fmt, num::NonZeroU32};
+use std::{fmt, net::IpAddr, num::NonZeroU32};
 
 use config::{Config, ConfigError, Environment};
+use jsonwebtoken::Algorithm;
 use serde::{de, Deserialize};
 
+use crate::auth::password_data::{PasswordAlgorithm, PasswordParams};
+
 #[derive(Debug, Deserialize)]
 pub struct AppConfig {
     pub database_url: String,
     pub pbkdf2_salt_size: usize,
     pub pbkdf2_iterations: NonZeroU32,
+    #[serde(default)]
+    pub password_algorithm: PasswordAlgorithm,
+    #[serde(default = "default_argon2_iterations")]
+    pub argon2_iterations: NonZeroU32,
+    #[serde(default = "default_argon2_memory_cost")]
+    pub argon2_memory_cost: u32,
+    #[serde(default = "default_argon2_parallelism")]
+    pub argon2_parallelism: u32,
     #[serde(deserialize_with = "deserialize_base64_string")]
     pub jwt_secret: Vec<u8>,
+    pub jwt_signing_key: Option<JwtSigningKeyConfig>,
+    pub jwks_path: Option<String>,
+    // kid가 없는 HS512 토큰 허용 여부, 지정하지 않으면 서명 키가 없을 때만 허용
+    pub jwt_legacy_fallback: Option<bool>,
+    #[serde(default = "default_jwt_issuer")]
+    pub jwt_issuer: String,
+    #[serde(default = "default_jwt_audience")]
+    pub jwt_audience: String,
+    #[serde(default = "default_access_token_lifetime")]
+    pub access_token_lifetime: i64,
     pub refresh_token_size: usize,
+    #[serde(default = "default_refresh_token_lifetime")]
+    pub refresh_token_lifetime: i64,
+    #[serde(default = "default_refresh_token_renewal_window")]
+    pub refresh_token_renewal_window: i64,
     pub redis: deadpool_redis::Config,
+    #[serde(default)]
+    pub chat_backend: ChatBackendConfig,
+    #[serde(default)]
+    pub oauth: OAuthConfig,
+    #[serde(default)]
+    pub mailer: MailerConfig,
+    #[serde(default)]
+    pub require_email_verification: bool,
+    #[serde(default = "default_email_verification_ttl")]
+    pub email_verification_ttl: i64,
+    #[serde(default = "default_verification_resend_cooldown")]
+    pub verification_resend_cooldown: i64,
+    #[serde(default = "default_verification_resend_ip_limit")]
+    pub verification_resend_ip_limit: i64,
+    #[serde(default = "default_verification_resend_ip_window")]
+    pub verification_resend_ip_window: i64,
+    #[serde(default = "default_password_reset_ttl")]
+    pub password_reset_ttl: i64,
+    #[serde(default)]
+    pub login_throttle: LoginThrottleConfig,
+    // 쉼표로 구분된 리버스 프록시 IP 목록, 이 주소에서 온 요청만 X-Forwarded-For를 신뢰
+    #[serde(default, deserialize_with = "deserialize_ip_list")]
+    pub trusted_proxies: Vec<IpAddr>,
+    #[serde(default = "default_totp_issuer")]
+    pub totp_issuer: String,
+    #[serde(default = "default_two_factor_challenge_ttl")]
+    pub two_factor_challenge_ttl: i64,
+    // 기간 내에 이 횟수만큼 2단계 인증에 실패하면 기간이 끝날 때까지 잠금
+    #[serde(default = "default_two_factor_max_failures")]
+    pub two_factor_max_failures: i64,
+    #[serde(default = "default_two_factor_failure_window")]
+    pub two_factor_failure_window: i64,
+    #[serde(default = "default_account_deletion_grace_period")]
+    pub account_deletion_grace_period: i64,
+    #[serde(default = "default_account_purge_interval")]
+    pub account_purge_interval: u64,
+    #[serde(default = "default_nickname_change_cooldown")]
+    pub nickname_change_cooldown: i64,
+    #[serde(default = "default_guest_max_inactive_age")]
+    pub guest_max_inactive_age: i64,
+    #[serde(default = "default_guest_creation_limit")]
+    pub guest_creation_limit: i64,
+    #[serde(default = "default_guest_creation_window")]
+    pub guest_creation_window: i64,
+    #[serde(default = "default_game_authorization_code_ttl")]
+    pub game_authorization_code_ttl: i64,
+    #[serde(default = "default_ws_auth_check_interval")]
+    pub ws_auth_check_interval: u64,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct LoginThrottleConfig {
+    pub identifier_threshold: i64,
+    pub ip_threshold: i64,
+    pub base_lockout: i64,
+    pub max_lockout: i64,
+    pub failure_window: i64,
+}
+
+impl Default for LoginThrottleConfig {
+    fn default() -> Self {
+        LoginThrottleConfig {
+            identifier_threshold: 5,
+            ip_threshold: 20,
+            base_lockout: 30,
+            max_lockout: 60 * 60,
+            failure_window: 60 * 60 * 24,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum MailerConfig {
+    Log,
+    File { dir: String },
+}
+
+impl Default for MailerConfig {
+    fn default() -> Self {
+        MailerConfig::Log
+    }
+}
+
+// 여러 인스턴스를 운영한다면 redis 백엔드를 사용
+// sqs는 큐의 메시지가 하나의 인스턴스에만 전달되므로 단일 인스턴스에서만 사용
+// sqs 기능 없이 빌드된 경우 sqs 설정은 알 수 없는 값으로 처리되어 설정 로드에 실패함
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum ChatBackendConfig {
+    Memory,
+    Redis,
+    #[cfg(feature = "sqs")]
+    Sqs {
+        endpoint: String,
+        region: String,
+        queue_url: String,
+    },
+}
+
+impl Default for ChatBackendConfig {
+    fn default() -> Self {
+        ChatBackendConfig::Memory
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct JwtSigningKeyConfig {
+    pub kid: String,
+    pub algorithm: Algorithm,
+    pub private_key_path: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct OAuthConfig {
+    pub kakao: Option<OAuthProviderConfig>,
+    pub google: Option<OAuthProviderConfig>,
+    pub facebook: Option<OAuthProviderConfig>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OAuthProviderConfig {
+    pub client_id: String,
+    pub client_secret: String,
+    pub redirect_uri: String,
+    pub token_url: Option<String>,
+    pub user_info_url: Option<String>,
 }
 
 impl AppConfig {
@@ -20,6 +173,119 @@ impl AppConfig {
         cfg.merge(Environment::new().separator("__"))?;
         cfg.try_into()
     }
+
+    pub fn password_params(&self) -> PasswordParams {
+        match self.password_algorithm {
+            PasswordAlgorithm::Pbkdf2 => PasswordParams::Pbkdf2 {
+                iterations: self.pbkdf2_iterations,
+            },
+            PasswordAlgorithm::Argon2id => PasswordParams::Argon2id {
+                iterations: self.argon2_iterations,
+                memory_cost: self.argon2_memory_cost,
+                parallelism: self.argon2_parallelism,
+            },
+        }
+    }
+}
+
+fn default_argon2_iterations() -> NonZeroU32 {
+    NonZeroU32::new(2).unwrap()
+}
+
+fn default_argon2_memory_cost() -> u32 {
+    19 * 1024
+}
+
+fn default_argon2_parallelism() -> u32 {
+    1
+}
+
+fn default_jwt_issuer() -> String {
+    "webgame-collection-api".to_owned()
+}
+
+fn default_jwt_audience() -> String {
+    "webgame-collection".to_owned()
+}
+
+fn default_access_token_lifetime() -> i64 {
+    60 * 60
+}
+
+fn default_refresh_token_lifetime() -> i64 {
+    60 * 60 * 24 * 30
+}
+
+fn default_refresh_token_renewal_window() -> i64 {
+    60 * 60 * 24 * 10
+}
+
+fn default_email_verification_ttl() -> i64 {
+    60 * 60 * 24
+}
+
+fn default_verification_resend_cooldown() -> i64 {
+    60
+}
+
+fn default_verification_resend_ip_limit() -> i64 {
+    10
+}
+
+fn default_verification_resend_ip_window() -> i64 {
+    60 * 60
+}
+
+fn default_password_reset_ttl() -> i64 {
+    60 * 60
+}
+
+fn default_totp_issuer() -> String {
+    "webgame-collection".to_owned()
+}
+
+fn default_two_factor_challenge_ttl() -> i64 {
+    60 * 5
+}
+
+fn default_two_factor_max_failures() -> i64 {
+    10
+}
+
+fn default_two_factor_failure_window() -> i64 {
+    60 * 15
+}
+
+fn default_account_deletion_grace_period() -> i64 {
+    60 * 60 * 24 * 30
+}
+
+fn default_account_purge_interval() -> u64 {
+    60 * 60
+}
+
+fn default_nickname_change_cooldown() -> i64 {
+    60 * 60 * 24 * 30
+}
+
+fn default_guest_max_inactive_age() -> i64 {
+    60 * 60 * 24 * 30
+}
+
+fn default_guest_creation_limit() -> i64 {
+    10
+}
+
+fn default_guest_creation_window() -> i64 {
+    60 * 60
+}
+
+fn default_game_authorization_code_ttl() -> i64 {
+    60
+}
+
+fn default_ws_auth_check_interval() -> u64 {
+    60
 }
 
 fn deserialize_base64_string<'de, D>(deserializer: D) -> Result<Vec<u8>, D::Error>
@@ -46,6 +312,34 @@ where
     deserializer.deserialize_any(Base64StringVisitor)
 }
 
+fn deserialize_ip_list<'de, D>(deserializer: D) -> Result<Vec<IpAddr>, D::Error>
+where
+    D: de::Deserializer<'de>,
+{
+    struct IpListVisitor;
+
+    impl<'de> de::Visitor<'de> for IpListVisitor {
+        type Value = Vec<IpAddr>;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("a comma separated list of IP addresses")
+        }
+
+        fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+        where
+            E: de::Error,
+        {
+            v.split(',')
+                .map(str::trim)
+                .filter(|ip| !ip.is_empty())
+                .map(|ip| ip.parse().map_err(E::custom))
+                .collect()
+        }
+    }
+
+    deserializer.deserialize_any(IpListVisitor)
+}
+
 lazy_static::lazy_static! {
     pub static ref CONFIG: AppConfig = {
         dotenv::dotenv().ok();
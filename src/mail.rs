@@ -0,0 +1,67 @@
+use std::{path::PathBuf, sync::Arc};
+
+use async_trait::async_trait;
+use chrono::Utc;
+use webgame_collection_api_macros::Error;
+
+use crate::config::{MailerConfig, CONFIG};
+
+pub struct Mail {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+#[derive(Error)]
+pub enum MailError {
+    #[error(message = "Failed to write the mail")]
+    IoError(std::io::Error),
+}
+
+#[async_trait]
+pub trait Mailer: Send + Sync {
+    async fn send(&self, mail: Mail) -> Result<(), MailError>;
+}
+
+pub type MailerRef = Arc<dyn Mailer>;
+
+pub fn build_mailer() -> MailerRef {
+    match &CONFIG.mailer {
+        MailerConfig::Log => Arc::new(LogMailer),
+        MailerConfig::File { dir } => Arc::new(FileMailer {
+            dir: PathBuf::from(dir),
+        }),
+    }
+}
+
+pub struct LogMailer;
+
+#[async_trait]
+impl Mailer for LogMailer {
+    async fn send(&self, mail: Mail) -> Result<(), MailError> {
+        log::info!("Mail to {} - {}\n{}", mail.to, mail.subject, mail.body);
+        Ok(())
+    }
+}
+
+pub struct FileMailer {
+    dir: PathBuf,
+}
+
+#[async_trait]
+impl Mailer for FileMailer {
+    async fn send(&self, mail: Mail) -> Result<(), MailError> {
+        let file_name = format!("{}-{}.txt", Utc::now().format("%Y%m%d%H%M%S%f"), mail.to);
+        let content = format!(
+            "To: {}\nSubject: {}\n\n{}\n",
+            mail.to, mail.subject, mail.body
+        );
+
+        tokio::fs::create_dir_all(&self.dir)
+            .await
+            .map_err(MailError::IoError)?;
+        tokio::fs::write(self.dir.join(file_name), content)
+            .await
+            .map_err(MailError::IoError)
+    }
+}
@@ -1,20 +1,26 @@
 mod schema;
+mod ws;
 
 pub mod auth;
 pub mod chat;
 pub mod config;
 pub mod error;
+pub mod mail;
 
 use actix_web::{
     guard::Header, middleware::Logger, web, App, HttpRequest, HttpResponse, HttpServer, Result,
 };
 use async_graphql::Data;
-use async_graphql_actix_web::{Request, Response, WSSubscription};
-use auth::auth_info::AuthInfo;
-use chat::ChatData;
+use async_graphql_actix_web::{Request, Response};
+use auth::{
+    auth_info::AuthInfo,
+    client_info::ClientInfo,
+    connection_auth::{ConnectionAuth, ConnectionAuthExtension},
+    jwt::JWT_KEYS,
+};
+use chat::ChatTransportRef;
 use sqlx::postgres::PgPoolOptions;
 use sqlx::PgPool;
-use tokio::sync::mpsc::{self, Sender};
 
 use crate::config::CONFIG;
 use crate::schema::{
@@ -32,20 +38,23 @@ async fn playground_handler() -> Result<HttpResponse> {
         )))
 }
 
+async fn jwks_handler() -> HttpResponse {
+    HttpResponse::Ok().json(&JWT_KEYS.jwks)
+}
+
 async fn graphql_handler(
     schema: web::Data<AppSchema>,
     req: Request,
     mut auth_info: AuthInfo,
-    chat_tx: web::Data<Sender<ChatData>>,
+    client_info: ClientInfo,
     redis_pool: web::Data<deadpool_redis::Pool>,
 ) -> Response {
-    let cloned = chat_tx.get_ref().clone();
     if let Ok(ref mut redis_conn) = redis_pool.get().await {
         auth_info.verify(redis_conn).await;
     }
 
     schema
-        .execute(req.into_inner().data(auth_info).data(cloned))
+        .execute(req.into_inner().data(auth_info).data(client_info))
         .await
         .into()
 }
@@ -54,32 +63,44 @@ async fn subscription_handler(
     schema: web::Data<AppSchema>,
     req: HttpRequest,
     stream: web::Payload,
-    chat_tx: web::Data<Sender<ChatData>>,
+    postgres_pool: web::Data<PgPool>,
     redis_pool: web::Data<deadpool_redis::Pool>,
 ) -> Result<HttpResponse> {
-    let cloned = chat_tx.get_ref().clone();
+    let client_info = ClientInfo::from_http_request(&req);
+    // 연결 중의 operation들은 AuthInfo 대신 이 상태를 통해 인증됨
+    let connection_auth = ConnectionAuth::new(AuthInfo::from_header(None));
 
-    WSSubscription::start_with_initializer(
+    ws::start_subscription(
         AppSchema::clone(&*schema),
         &req,
         stream,
+        connection_auth.clone(),
+        PgPool::clone(&*postgres_pool),
+        deadpool_redis::Pool::clone(&*redis_pool),
         |value| async move {
             let mut data = Data::default();
 
-            let mut auth_info = AuthInfo::from_header(
-                value
-                    .as_object()
-                    .and_then(|m| m.get("Authorization"))
-                    .and_then(|v| v.as_str())
-                    .map(|s| s.to_string()),
-            );
+            let authorization = value
+                .as_object()
+                .and_then(|m| m.get("Authorization"))
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string());
+
+            let mut auth_info = match authorization
+                .as_deref()
+                .and_then(|s| s.strip_prefix("ApiKey "))
+            {
+                Some(api_key) => AuthInfo::from_api_key(&postgres_pool, api_key).await,
+                None => AuthInfo::from_header(authorization),
+            };
 
             if let Ok(ref mut redis_conn) = redis_pool.get().await {
                 auth_info.verify(redis_conn).await;
             }
 
-            data.insert(auth_info);
-            data.insert(cloned);
+            connection_auth.init(auth_info).await;
+            data.insert(connection_auth);
+            data.insert(client_info);
             Ok(data)
         },
     )
@@ -87,27 +108,40 @@ async fn subscription_handler(
 
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
-    std::env::set_var("RUST_LOG", "actix_server=info,actix_web=info");
+    std::env::set_var(
+        "RUST_LOG",
+        "actix_server=info,actix_web=info,webgame_collection_api=info",
+    );
     env_logger::init();
 
-    #[cfg(not(feature = "sqs"))]
-    let (chat_tx, chat_handle) = {
-        let (tx, rx) = mpsc::channel(64);
-        (tx, tokio::spawn(chat::broadcast(rx)))
-    };
+    lazy_static::initialize(&JWT_KEYS);
 
     let postgres_pool = build_postgres_pool().await;
     let redis_pool = build_redis_pool();
 
-    let schema_data = web::Data::new(build_schema(postgres_pool, redis_pool.clone()).await);
-    let chat_tx_data = web::Data::new(chat_tx.clone());
+    let chat_transport = chat::build_chat_transport(redis_pool.clone());
+    let chat_handle = tokio::spawn({
+        let chat_transport = chat_transport.clone();
+        async move { chat_transport.run().await }
+    });
+
+    let purge_handle = tokio::spawn(auth::account::run_purge_job(
+        postgres_pool.clone(),
+        redis_pool.clone(),
+    ));
+
+    let postgres_pool_data = web::Data::new(postgres_pool.clone());
+    let schema_data =
+        web::Data::new(build_schema(postgres_pool, redis_pool.clone(), chat_transport).await);
     let redis_pool_data = web::Data::new(redis_pool);
 
     let actix_result = HttpServer::new(move || {
         let app = App::new()
             .app_data(schema_data.clone())
+            .app_data(postgres_pool_data.clone())
             .app_data(redis_pool_data.clone())
             .wrap(Logger::default())
+            .route("/.well-known/jwks.json", web::get().to(jwks_handler))
             .route("/graphql", web::post().to(graphql_handler))
             .route(
                 "/graphql",
@@ -116,9 +150,6 @@ async fn main() -> std::io::Result<()> {
                     .to(subscription_handler),
             );
 
-        #[cfg(not(feature = "sqs"))]
-        let app = app.app_data(chat_tx_data.clone());
-
         #[cfg(feature = "playground")]
         let app = app.route("/graphql", web::get().to(playground_handler));
 
@@ -129,11 +160,16 @@ async fn main() -> std::io::Result<()> {
     .await;
 
     chat_handle.abort();
+    purge_handle.abort();
 
     actix_result
 }
 
-async fn build_schema(postgres_pool: PgPool, redis_pool: deadpool_redis::Pool) -> AppSchema {
+async fn build_schema(
+    postgres_pool: PgPool,
+    redis_pool: deadpool_redis::Pool,
+    chat_transport: ChatTransportRef,
+) -> AppSchema {
     AppSchema::build(
         QueryRoot::default(),
         MutationRoot::default(),
@@ -141,6 +177,9 @@ async fn build_schema(postgres_pool: PgPool, redis_pool: deadpool_redis::Pool) -
     )
     .data(postgres_pool)
     .data(redis_pool)
+    .data(mail::build_mailer())
+    .data(chat_transport)
+    .extension(ConnectionAuthExtension)
     .finish()
 }
 
@@ -0,0 +1,65 @@
+use std::convert::TryFrom;
+
+use async_graphql::*;
+use sqlx::PgPool;
+use uuid::Uuid;
+use webgame_collection_api_macros::Error;
+
+use crate::{
+    auth::role::{get_user_roles, grant_role, revoke_role, Role, RoleGuard},
+    error::Error,
+    schema::types::node::{IdData, IdDataError, NodeIdent},
+};
+
+#[derive(Error)]
+enum AdminMutationError {
+    #[error(message = "Database error")]
+    DbError(sqlx::Error),
+    #[error(message = "ID parsing failed")]
+    IdParseError(IdDataError),
+    #[error(message = "The ID is not a user ID")]
+    NotUserId,
+}
+
+#[derive(Default)]
+pub struct AdminMutation;
+
+#[Object]
+impl AdminMutation {
+    #[graphql(guard(RoleGuard(role = "Role::Admin")))]
+    async fn grant_role(&self, ctx: &Context<'_>, user_id: ID, role: Role) -> Result<Vec<Role>> {
+        let pool = ctx.data::<PgPool>()?;
+        let user_id = parse_user_id(user_id)?;
+
+        grant_role(pool, &user_id, role)
+            .await
+            .map_err(|e| AdminMutationError::DbError(e).build())?;
+
+        get_user_roles(pool, &user_id)
+            .await
+            .map_err(|e| AdminMutationError::DbError(e).build())
+    }
+
+    #[graphql(guard(RoleGuard(role = "Role::Admin")))]
+    async fn revoke_role(&self, ctx: &Context<'_>, user_id: ID, role: Role) -> Result<Vec<Role>> {
+        let pool = ctx.data::<PgPool>()?;
+        let user_id = parse_user_id(user_id)?;
+
+        revoke_role(pool, &user_id, role)
+            .await
+            .map_err(|e| AdminMutationError::DbError(e).build())?;
+
+        get_user_roles(pool, &user_id)
+            .await
+            .map_err(|e| AdminMutationError::DbError(e).build())
+    }
+}
+
+fn parse_user_id(id: ID) -> Result<Uuid> {
+    let id_data = IdData::try_from(id).map_err(|e| AdminMutationError::IdParseError(e).build())?;
+
+    match id_data.ty {
+        NodeIdent::User => Ok(id_data.uuid),
+        _ => Err(AdminMutationError::NotUserId.build()),
+    }
+}
@@ -0,0 +1,62 @@
+use async_graphql::*;
+use sqlx::PgPool;
+use uuid::Uuid;
+use webgame_collection_api_macros::Error;
+
+use crate::{
+    auth::{
+        api_key::{create_api_key, revoke_api_key},
+        auth_info::AuthInfo,
+        scope::Scope,
+    },
+    error::Error,
+    schema::types::{
+        scalars::DateTimeScalar,
+        user::{ApiKey, CreatedApiKey},
+    },
+};
+
+#[derive(Error)]
+enum ApiKeyMutationError {
+    #[error(message = "Invalid API key ID")]
+    InvalidKeyId,
+}
+
+#[derive(Default)]
+pub struct ApiKeyMutation;
+
+#[Object]
+impl ApiKeyMutation {
+    async fn create_api_key(
+        &self,
+        ctx: &Context<'_>,
+        name: String,
+        scopes: Vec<Scope>,
+        expires_at: Option<DateTimeScalar>,
+    ) -> Result<CreatedApiKey> {
+        let pool = ctx.data::<PgPool>()?;
+        let auth_info = ctx.data::<AuthInfo>()?;
+        let user_id = auth_info.get_user_id().map_err(|e| e.build())?;
+
+        let (data, key) = create_api_key(pool, &user_id, name, scopes, expires_at.map(|e| e.0))
+            .await
+            .map_err(|e| e.build())?;
+
+        Ok(CreatedApiKey {
+            api_key: ApiKey::from(data),
+            key,
+        })
+    }
+
+    async fn revoke_api_key(&self, ctx: &Context<'_>, id: ID) -> Result<bool> {
+        let pool = ctx.data::<PgPool>()?;
+        let auth_info = ctx.data::<AuthInfo>()?;
+        let user_id = auth_info.get_user_id().map_err(|e| e.build())?;
+        let key_id = Uuid::parse_str(&id).map_err(|_| ApiKeyMutationError::InvalidKeyId.build())?;
+
+        revoke_api_key(pool, &user_id, &key_id)
+            .await
+            .map(|_| true)
+            .map_err(|e| e.build())
+    }
+}
@@ -1,30 +1,62 @@
 use async_graphql::validators::Email;
 use async_graphql::*;
 use sqlx::PgPool;
+use uuid::Uuid;
 use webgame_collection_api_macros::Error;
 
 use crate::{
     auth::{
+        account::{delete_account, restore_account},
+        audit::{record_security_event, NewSecurityEvent, SecurityEventType},
         auth_info::AuthInfo,
-        login::{create_access_token, verify_auth_method},
+        client_info::ClientInfo,
+        connection_auth::ConnectionAuth,
+        guest::{check_guest_creation_limit, create_guest, touch_last_active, upgrade_guest},
+        link::{
+            confirm_email_link, get_auth_methods, link_auth_method, request_email_link,
+            unlink_auth_method,
+        },
+        login::{create_access_token, find_email_user_id, login_email, verify_auth_method},
+        oauth::exchange_code,
+        password::{change_password, request_password_reset, reset_password},
         password_data::PasswordData,
         refresh::{
-            check_refresh, create_refresh_token, register_refresh_token, RefreshCheckResult,
+            create_refresh_token, register_refresh_token, revoke_refresh_token_family,
+            rotate_refresh_token, RotatedRefreshToken,
         },
         register::register,
+        role::get_user_roles,
+        session::{revoke_all_sessions, revoke_session},
+        two_factor::{
+            confirm_totp, create_two_factor_challenge, disable_two_factor, enroll_totp,
+            is_two_factor_enabled, verify_two_factor_challenge, TotpEnrollment,
+        },
+        verification::{resend_verification_email, send_verification_email, verify_email},
         AuthMethodType,
     },
     config::CONFIG,
     error::Error,
-    schema::types::user::{LoginResult, RefreshResult, User, UserRegisterInput},
+    mail::MailerRef,
+    schema::types::user::{
+        AuthMethod, LinkAuthMethodInput, LoginPayload, LoginResult, OAuthProvider, RefreshResult,
+        TwoFactorChallenge, TwoFactorEnrollment, User, UserRegisterInput,
+    },
 };
 
 #[derive(Error)]
 pub enum AuthMutationError {
+    #[error(message = "Database error")]
+    DbError(sqlx::Error),
     #[error(message = "Redis error")]
     RedisError(redis::RedisError),
     #[error(message = "Token creation failed")]
     TokenCreationFailed,
+    #[error(message = "Credential required for the auth method type not provided")]
+    CredentialNotProvided,
+    #[error(message = "Invalid session ID")]
+    InvalidSessionId,
+    #[error(message = "User not found")]
+    UserNotFound,
 }
 
 #[derive(Default)]
@@ -40,19 +72,79 @@ impl AuthMutation {
         password: String,
     ) -> Result<User> {
         let pool = ctx.data::<PgPool>()?;
+        let mailer = ctx.data::<MailerRef>()?;
+        let mut redis_conn = ctx.data::<deadpool_redis::Pool>()?.get().await?;
 
         let password_data =
-            PasswordData::new(&password, CONFIG.pbkdf2_salt_size, CONFIG.pbkdf2_iterations)
+            PasswordData::new(&password, CONFIG.pbkdf2_salt_size, CONFIG.password_params())
                 .map_err(|e| e.build())?;
 
-        register(
+        let user = match register(
             pool,
             input,
             AuthMethodType::Email,
-            email,
+            email.clone(),
             Some(password_data),
         )
         .await
+        {
+            Ok(user) => user,
+            Err(e) => {
+                let event = NewSecurityEvent::failure(SecurityEventType::Register, e.message());
+                audit(ctx, event.identifier(email)).await;
+                return Err(e.build());
+            }
+        };
+        audit(
+            ctx,
+            NewSecurityEvent::success(SecurityEventType::Register, user.uuid).identifier(email),
+        )
+        .await;
+
+        // 가입은 이미 완료되었으므로 메일 발송에 실패해도 유저를 반환하고,
+        // 인증 메일은 resendVerificationEmail로 다시 받을 수 있음
+        if let Err(e) =
+            send_verification_email(mailer.as_ref(), &mut redis_conn, &user.uuid, &user.email).await
+        {
+            log::warn!(
+                "Failed to send the verification email to {}: {}",
+                user.uuid,
+                e.message()
+            );
+        }
+
+        Ok(user)
+    }
+
+    async fn verify_email(&self, ctx: &Context<'_>, token: String) -> Result<bool> {
+        let pool = ctx.data::<PgPool>()?;
+        let mut redis_conn = ctx.data::<deadpool_redis::Pool>()?.get().await?;
+
+        verify_email(pool, &mut redis_conn, &token)
+            .await
+            .map(|_| true)
+            .map_err(|e| e.build())
+    }
+
+    async fn resend_verification_email(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(validator(Email))] email: String,
+    ) -> Result<bool> {
+        let pool = ctx.data::<PgPool>()?;
+        let mailer = ctx.data::<MailerRef>()?;
+        let client_info = ctx.data::<ClientInfo>()?;
+        let mut redis_conn = ctx.data::<deadpool_redis::Pool>()?.get().await?;
+
+        resend_verification_email(
+            pool,
+            mailer.as_ref(),
+            &mut redis_conn,
+            &email,
+            client_info.ip.as_deref(),
+        )
+        .await
+        .map(|_| true)
         .map_err(|e| e.build())
     }
 
@@ -61,80 +153,564 @@ impl AuthMutation {
         ctx: &Context<'_>,
         #[graphql(validator(Email))] email: String,
         password: String,
-    ) -> Result<Option<LoginResult>> {
+    ) -> Result<Option<LoginPayload>> {
         let pg_pool = ctx.data::<PgPool>()?;
+        let client_info = ctx.data::<ClientInfo>()?;
         let mut redis_conn = ctx.data::<deadpool_redis::Pool>()?.get().await?;
 
-        let user_id = verify_auth_method(pg_pool, AuthMethodType::Email, email, Some(password))
+        let user_id = match login_email(
+            pg_pool,
+            &mut redis_conn,
+            client_info,
+            email.clone(),
+            password,
+        )
+        .await
+        {
+            Ok(user_id) => user_id,
+            Err(e) => {
+                // 존재하는 계정에 대한 실패는 해당 유저의 이벤트로 남김
+                let user_id = find_email_user_id(pg_pool, &email).await.ok().flatten();
+                let event = NewSecurityEvent::failure(SecurityEventType::Login, e.message());
+                audit(ctx, event.user_id(user_id).identifier(email)).await;
+                return Err(e.build());
+            }
+        };
+
+        issue_login_payload(ctx, &user_id).await.map(Some)
+    }
+
+    #[graphql(name = "registerOAuth")]
+    async fn register_oauth(
+        &self,
+        ctx: &Context<'_>,
+        input: UserRegisterInput,
+        provider: OAuthProvider,
+        code: String,
+    ) -> Result<User> {
+        let pool = ctx.data::<PgPool>()?;
+
+        let identifier = exchange_code(provider, &code)
             .await
             .map_err(|e| e.build())?;
 
-        let access_token = create_access_token(&user_id, &CONFIG.jwt_secret)
-            .ok_or(AuthMutationError::TokenCreationFailed.build())?;
-        let refresh_token = create_refresh_token(CONFIG.refresh_token_size)
-            .ok_or(AuthMutationError::TokenCreationFailed.build())?;
+        match register(pool, input, provider.into(), identifier, None).await {
+            Ok(user) => {
+                audit(
+                    ctx,
+                    NewSecurityEvent::success(SecurityEventType::Register, user.uuid),
+                )
+                .await;
+                Ok(user)
+            }
+            Err(e) => {
+                audit(
+                    ctx,
+                    NewSecurityEvent::failure(SecurityEventType::Register, e.message()),
+                )
+                .await;
+                Err(e.build())
+            }
+        }
+    }
+
+    #[graphql(name = "loginOAuth")]
+    async fn login_oauth(
+        &self,
+        ctx: &Context<'_>,
+        provider: OAuthProvider,
+        code: String,
+    ) -> Result<Option<LoginPayload>> {
+        let pg_pool = ctx.data::<PgPool>()?;
 
-        register_refresh_token(&refresh_token, &mut redis_conn)
+        let identifier = exchange_code(provider, &code)
             .await
-            .map_err(|e| AuthMutationError::RedisError(e).build())?;
+            .map_err(|e| e.build())?;
+        let user_id = match verify_auth_method(pg_pool, provider.into(), identifier, None).await {
+            Ok(user_id) => user_id,
+            Err(e) => {
+                audit(
+                    ctx,
+                    NewSecurityEvent::failure(SecurityEventType::Login, e.message()),
+                )
+                .await;
+                return Err(e.build());
+            }
+        };
 
-        Ok(Some(LoginResult {
-            access_token,
-            refresh_token,
-        }))
+        issue_login_payload(ctx, &user_id).await.map(Some)
+    }
+
+    async fn verify_two_factor(
+        &self,
+        ctx: &Context<'_>,
+        challenge_token: String,
+        code: String,
+    ) -> Result<LoginResult> {
+        let pool = ctx.data::<PgPool>()?;
+        let mut redis_conn = ctx.data::<deadpool_redis::Pool>()?.get().await?;
+
+        let user_id =
+            match verify_two_factor_challenge(pool, &mut redis_conn, &challenge_token, &code).await
+            {
+                Ok(user_id) => user_id,
+                Err(e) => {
+                    audit(
+                        ctx,
+                        NewSecurityEvent::failure(SecurityEventType::TwoFactor, e.message()),
+                    )
+                    .await;
+                    return Err(e.build());
+                }
+            };
+
+        issue_login_result(ctx, &user_id).await
+    }
+
+    async fn enroll_two_factor(&self, ctx: &Context<'_>) -> Result<TwoFactorEnrollment> {
+        let pool = ctx.data::<PgPool>()?;
+        let auth_info = ctx.data::<AuthInfo>()?;
+        let user_id = auth_info.get_user_id().map_err(|e| e.build())?;
+
+        let TotpEnrollment { secret, uri } =
+            enroll_totp(pool, &user_id).await.map_err(|e| e.build())?;
+
+        Ok(TwoFactorEnrollment { secret, uri })
+    }
+
+    async fn confirm_two_factor(&self, ctx: &Context<'_>, code: String) -> Result<Vec<String>> {
+        let pool = ctx.data::<PgPool>()?;
+        let auth_info = ctx.data::<AuthInfo>()?;
+        let user_id = auth_info.get_user_id().map_err(|e| e.build())?;
+
+        confirm_totp(pool, &user_id, &code)
+            .await
+            .map_err(|e| e.build())
+    }
+
+    async fn disable_two_factor(&self, ctx: &Context<'_>, code: String) -> Result<bool> {
+        let pool = ctx.data::<PgPool>()?;
+        let auth_info = ctx.data::<AuthInfo>()?;
+        let user_id = auth_info.get_user_id().map_err(|e| e.build())?;
+        let mut redis_conn = ctx.data::<deadpool_redis::Pool>()?.get().await?;
+
+        disable_two_factor(pool, &mut redis_conn, &user_id, &code)
+            .await
+            .map(|_| true)
+            .map_err(|e| e.build())
+    }
+
+    async fn login_as_guest(&self, ctx: &Context<'_>) -> Result<LoginResult> {
+        let pool = ctx.data::<PgPool>()?;
+        let client_info = ctx.data::<ClientInfo>()?;
+        let mut redis_conn = ctx.data::<deadpool_redis::Pool>()?.get().await?;
+
+        check_guest_creation_limit(client_info.ip.as_deref(), &mut redis_conn)
+            .await
+            .map_err(|e| e.build())?;
+
+        let user_id = create_guest(pool).await.map_err(|e| e.build())?;
+
+        issue_login_result(ctx, &user_id).await
+    }
+
+    async fn upgrade_guest(
+        &self,
+        ctx: &Context<'_>,
+        input: UserRegisterInput,
+        method: LinkAuthMethodInput,
+    ) -> Result<User> {
+        let pool = ctx.data::<PgPool>()?;
+        let auth_info = ctx.data::<AuthInfo>()?;
+        let mailer = ctx.data::<MailerRef>()?;
+        let mut redis_conn = ctx.data::<deadpool_redis::Pool>()?.get().await?;
+        let user_id = auth_info.get_user_id().map_err(|e| e.build())?;
+
+        let ty = method.ty;
+        let (identifier, password_data) = resolve_credentials(method).await?;
+
+        let user = upgrade_guest(pool, &user_id, input, ty, identifier, password_data)
+            .await
+            .map_err(|e| e.build())?;
+
+        // 가입은 이미 완료되었으므로 메일 발송에 실패해도 유저를 반환하고,
+        // 인증 메일은 resendVerificationEmail로 다시 받을 수 있음
+        if let Err(e) =
+            send_verification_email(mailer.as_ref(), &mut redis_conn, &user.uuid, &user.email).await
+        {
+            log::warn!(
+                "Failed to send the verification email to {}: {}",
+                user.uuid,
+                e.message()
+            );
+        }
+
+        Ok(user)
+    }
+
+    async fn link_auth_method(
+        &self,
+        ctx: &Context<'_>,
+        input: LinkAuthMethodInput,
+    ) -> Result<Vec<AuthMethod>> {
+        let pool = ctx.data::<PgPool>()?;
+        let auth_info = ctx.data::<AuthInfo>()?;
+        let user_id = auth_info.get_user_id().map_err(|e| e.build())?;
+
+        let ty = input.ty;
+        if ty == AuthMethodType::Email {
+            let mut redis_conn = ctx.data::<deadpool_redis::Pool>()?.get().await?;
+
+            // 이메일 연결은 메일로 받은 코드를 함께 보내야 완료됨
+            match input.code {
+                Some(code) => confirm_email_link(pool, &mut redis_conn, &user_id, &code)
+                    .await
+                    .map_err(|e| e.build())?,
+                None => {
+                    let mailer = ctx.data::<MailerRef>()?;
+                    let (email, password_data) = resolve_credentials(input).await?;
+                    let password_data =
+                        password_data.ok_or(AuthMutationError::CredentialNotProvided.build())?;
+
+                    request_email_link(
+                        pool,
+                        mailer.as_ref(),
+                        &mut redis_conn,
+                        &user_id,
+                        email,
+                        password_data,
+                    )
+                    .await
+                    .map_err(|e| e.build())?;
+                }
+            }
+        } else {
+            let (identifier, password_data) = resolve_credentials(input).await?;
+
+            link_auth_method(pool, &user_id, ty, identifier, password_data)
+                .await
+                .map_err(|e| e.build())?;
+        }
+
+        get_auth_methods(pool, &user_id)
+            .await
+            .map_err(|e| e.build())
+    }
+
+    async fn unlink_auth_method(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(name = "type")] ty: AuthMethodType,
+        identifier: String,
+    ) -> Result<Vec<AuthMethod>> {
+        let pool = ctx.data::<PgPool>()?;
+        let auth_info = ctx.data::<AuthInfo>()?;
+        let user_id = auth_info.get_user_id().map_err(|e| e.build())?;
+
+        unlink_auth_method(pool, &user_id, ty, identifier)
+            .await
+            .map_err(|e| e.build())?;
+
+        get_auth_methods(pool, &user_id)
+            .await
+            .map_err(|e| e.build())
+    }
+
+    async fn change_password(
+        &self,
+        ctx: &Context<'_>,
+        old_password: String,
+        new_password: String,
+    ) -> Result<bool> {
+        let pool = ctx.data::<PgPool>()?;
+        let auth_info = ctx.data::<AuthInfo>()?;
+        let mut redis_conn = ctx.data::<deadpool_redis::Pool>()?.get().await?;
+        let user_id = auth_info.get_user_id().map_err(|e| e.build())?;
+
+        let result = change_password(
+            pool,
+            &mut redis_conn,
+            &user_id,
+            &old_password,
+            &new_password,
+        )
+        .await;
+
+        let event = match &result {
+            Ok(_) => NewSecurityEvent::success(SecurityEventType::PasswordChange, user_id),
+            Err(e) => NewSecurityEvent::failure(SecurityEventType::PasswordChange, e.message())
+                .user_id(Some(user_id)),
+        };
+        audit(ctx, event).await;
+
+        result.map(|_| true).map_err(|e| e.build())
+    }
+
+    async fn request_password_reset(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(validator(Email))] email: String,
+    ) -> Result<bool> {
+        let pool = ctx.data::<PgPool>()?;
+        let mailer = ctx.data::<MailerRef>()?;
+        let mut redis_conn = ctx.data::<deadpool_redis::Pool>()?.get().await?;
+
+        request_password_reset(pool, mailer.as_ref(), &mut redis_conn, &email)
+            .await
+            .map(|_| true)
+            .map_err(|e| e.build())
+    }
+
+    async fn reset_password(
+        &self,
+        ctx: &Context<'_>,
+        token: String,
+        new_password: String,
+    ) -> Result<bool> {
+        let pool = ctx.data::<PgPool>()?;
+        let mut redis_conn = ctx.data::<deadpool_redis::Pool>()?.get().await?;
+
+        reset_password(pool, &mut redis_conn, &token, &new_password)
+            .await
+            .map(|_| true)
+            .map_err(|e| e.build())
     }
 
     async fn logout(&self, ctx: &Context<'_>) -> Result<bool> {
         let auth_info = ctx.data::<AuthInfo>()?;
         let mut redis_conn = ctx.data::<deadpool_redis::Pool>()?.get().await?;
 
+        if let (Ok(user_id), Some(session_id)) = (auth_info.get_user_id(), auth_info.session_id()) {
+            revoke_session(&user_id, &session_id, &mut redis_conn)
+                .await
+                .map_err(|e| e.build())?;
+        }
+        if let Ok(user_id) = auth_info.get_user_id() {
+            audit(
+                ctx,
+                NewSecurityEvent::success(SecurityEventType::Logout, user_id),
+            )
+            .await;
+        }
+
         auth_info
             .invalidate(&mut redis_conn)
             .await
             .map_err(|e| AuthMutationError::RedisError(e).build())
     }
 
+    async fn revoke_session(&self, ctx: &Context<'_>, id: ID) -> Result<bool> {
+        let auth_info = ctx.data::<AuthInfo>()?;
+        let mut redis_conn = ctx.data::<deadpool_redis::Pool>()?.get().await?;
+        let user_id = auth_info.get_user_id().map_err(|e| e.build())?;
+        let session_id =
+            Uuid::parse_str(&id).map_err(|_| AuthMutationError::InvalidSessionId.build())?;
+
+        revoke_session(&user_id, &session_id, &mut redis_conn)
+            .await
+            .map(|_| true)
+            .map_err(|e| e.build())
+    }
+
+    async fn logout_everywhere(&self, ctx: &Context<'_>) -> Result<bool> {
+        let auth_info = ctx.data::<AuthInfo>()?;
+        let mut redis_conn = ctx.data::<deadpool_redis::Pool>()?.get().await?;
+        let user_id = auth_info.get_user_id().map_err(|e| e.build())?;
+
+        revoke_all_sessions(&user_id, &mut redis_conn)
+            .await
+            .map_err(|e| e.build())?;
+
+        auth_info
+            .invalidate(&mut redis_conn)
+            .await
+            .map_err(|e| AuthMutationError::RedisError(e).build())
+    }
+
+    async fn delete_account(&self, ctx: &Context<'_>) -> Result<bool> {
+        let pool = ctx.data::<PgPool>()?;
+        let auth_info = ctx.data::<AuthInfo>()?;
+        let mut redis_conn = ctx.data::<deadpool_redis::Pool>()?.get().await?;
+        let user_id = auth_info.get_user_id().map_err(|e| e.build())?;
+
+        delete_account(pool, &mut redis_conn, &user_id)
+            .await
+            .map_err(|e| e.build())?;
+
+        auth_info
+            .invalidate(&mut redis_conn)
+            .await
+            .map_err(|e| AuthMutationError::RedisError(e).build())
+    }
+
+    // WebSocket 연결에서 구독을 유지한 채 access token만 교체
+    async fn refresh_connection_auth(
+        &self,
+        ctx: &Context<'_>,
+        access_token: String,
+    ) -> Result<bool> {
+        let connection_auth = ctx.data::<ConnectionAuth>()?;
+        let redis_pool = ctx.data::<deadpool_redis::Pool>()?;
+        let auth_info = AuthInfo::from_header(Some(format!("Bearer {}", access_token)));
+
+        connection_auth
+            .replace(redis_pool, auth_info)
+            .await
+            .map(|_| true)
+            .map_err(|e| e.build())
+    }
+
     async fn refresh_auth(
         &self,
         ctx: &Context<'_>,
         refresh_token: String,
-    ) -> Result<Option<RefreshResult>> {
+    ) -> Result<RefreshResult> {
+        let pool = ctx.data::<PgPool>()?;
         let auth_info = ctx.data::<AuthInfo>()?;
         let mut redis_conn = ctx.data::<deadpool_redis::Pool>()?.get().await?;
-        let user_id = auth_info.get_user_id().map_err(|e| e.build())?;
 
-        let refresh_check_result = check_refresh(&refresh_token, &mut redis_conn)
+        let RotatedRefreshToken {
+            user_id,
+            session_id,
+            refresh_token,
+        } = match rotate_refresh_token(&refresh_token, &mut redis_conn).await {
+            Ok(rotated) => rotated,
+            Err(e) => {
+                audit(
+                    ctx,
+                    NewSecurityEvent::failure(SecurityEventType::Refresh, e.message()),
+                )
+                .await;
+                return Err(e.build());
+            }
+        };
+
+        let active = touch_last_active(pool, &user_id)
             .await
-            .map_err(|e| AuthMutationError::RedisError(e).build())?;
+            .map_err(|e| AuthMutationError::DbError(e).build())?;
+        if !active {
+            revoke_refresh_token_family(&user_id, &session_id, &mut redis_conn)
+                .await
+                .map_err(|e| AuthMutationError::RedisError(e).build())?;
 
-        let access_token = match refresh_check_result {
-            RefreshCheckResult::Both | RefreshCheckResult::OnlyAccessToken => {
-                let token = create_access_token(&user_id, &CONFIG.jwt_secret)
-                    .ok_or(AuthMutationError::TokenCreationFailed.build())?;
+            let error = AuthMutationError::UserNotFound;
+            let event = NewSecurityEvent::failure(SecurityEventType::Refresh, error.message());
+            audit(ctx, event.user_id(Some(user_id))).await;
+            return Err(error.build());
+        }
 
-                auth_info
-                    .invalidate(&mut redis_conn)
-                    .await
-                    .map_err(|e| AuthMutationError::RedisError(e).build())?;
+        let roles = get_user_roles(pool, &user_id)
+            .await
+            .map_err(|e| AuthMutationError::DbError(e).build())?;
+        let access_token = create_access_token(&user_id, &session_id, roles)
+            .ok_or(AuthMutationError::TokenCreationFailed.build())?;
 
-                Some(token)
-            }
-            _ => None,
-        };
-        let refresh_token = match refresh_check_result {
-            RefreshCheckResult::Both => Some(
-                create_refresh_token(CONFIG.refresh_token_size)
-                    .ok_or(AuthMutationError::TokenCreationFailed.build())?,
-            ),
-            _ => None,
-        };
+        audit(
+            ctx,
+            NewSecurityEvent::success(SecurityEventType::Refresh, user_id),
+        )
+        .await;
 
-        match access_token {
-            Some(access_token) => Ok(Some(RefreshResult {
-                access_token,
-                refresh_token,
-            })),
-            None => Ok(None),
+        // 요청에 같은 유저의 기존 access token이 포함되어 있다면 함께 무효화
+        if matches!(auth_info.get_user_id(), Ok(id) if id == user_id) {
+            auth_info
+                .invalidate(&mut redis_conn)
+                .await
+                .map_err(|e| AuthMutationError::RedisError(e).build())?;
         }
+
+        Ok(RefreshResult {
+            access_token,
+            refresh_token,
+        })
+    }
+}
+
+async fn resolve_credentials(input: LinkAuthMethodInput) -> Result<(String, Option<PasswordData>)> {
+    match input.ty.oauth_provider() {
+        Some(provider) => {
+            let code = input
+                .code
+                .ok_or(AuthMutationError::CredentialNotProvided.build())?;
+            let identifier = exchange_code(provider, &code)
+                .await
+                .map_err(|e| e.build())?;
+
+            Ok((identifier, None))
+        }
+        None => {
+            let (email, password) = input
+                .email
+                .zip(input.password)
+                .ok_or(AuthMutationError::CredentialNotProvided.build())?;
+            let password_data =
+                PasswordData::new(&password, CONFIG.pbkdf2_salt_size, CONFIG.password_params())
+                    .map_err(|e| e.build())?;
+
+            Ok((email, Some(password_data)))
+        }
+    }
+}
+
+async fn issue_login_payload(ctx: &Context<'_>, user_id: &Uuid) -> Result<LoginPayload> {
+    let pool = ctx.data::<PgPool>()?;
+
+    let two_factor_enabled = is_two_factor_enabled(pool, user_id)
+        .await
+        .map_err(|e| AuthMutationError::DbError(e).build())?;
+    if !two_factor_enabled {
+        return issue_login_result(ctx, user_id)
+            .await
+            .map(LoginPayload::LoginResult);
+    }
+
+    let mut redis_conn = ctx.data::<deadpool_redis::Pool>()?.get().await?;
+    let challenge_token = create_two_factor_challenge(&mut redis_conn, user_id)
+        .await
+        .map_err(|e| e.build())?;
+
+    Ok(LoginPayload::TwoFactorChallenge(TwoFactorChallenge {
+        challenge_token,
+    }))
+}
+
+async fn issue_login_result(ctx: &Context<'_>, user_id: &Uuid) -> Result<LoginResult> {
+    let pool = ctx.data::<PgPool>()?;
+    let client_info = ctx.data::<ClientInfo>()?;
+    let mut redis_conn = ctx.data::<deadpool_redis::Pool>()?.get().await?;
+
+    let refresh_token = create_refresh_token(CONFIG.refresh_token_size)
+        .ok_or(AuthMutationError::TokenCreationFailed.build())?;
+    let session_id = register_refresh_token(&refresh_token, user_id, client_info, &mut redis_conn)
+        .await
+        .map_err(|e| AuthMutationError::RedisError(e).build())?;
+    let roles = get_user_roles(pool, user_id)
+        .await
+        .map_err(|e| AuthMutationError::DbError(e).build())?;
+    let access_token = create_access_token(user_id, &session_id, roles)
+        .ok_or(AuthMutationError::TokenCreationFailed.build())?;
+
+    // 삭제 유예 기간 중인 계정이라면 로그인과 함께 복구
+    restore_account(pool, user_id)
+        .await
+        .map_err(|e| AuthMutationError::DbError(e).build())?;
+    touch_last_active(pool, user_id)
+        .await
+        .map_err(|e| AuthMutationError::DbError(e).build())?;
+    audit(
+        ctx,
+        NewSecurityEvent::success(SecurityEventType::Login, *user_id),
+    )
+    .await;
+
+    Ok(LoginResult {
+        access_token,
+        refresh_token,
+    })
+}
+
+async fn audit(ctx: &Context<'_>, event: NewSecurityEvent) {
+    if let (Ok(pool), Ok(client_info)) = (ctx.data::<PgPool>(), ctx.data::<ClientInfo>()) {
+        record_security_event(pool, client_info, event).await;
     }
 }
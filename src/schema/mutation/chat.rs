@@ -1,15 +1,18 @@
 use std::convert::TryFrom;
 
 use async_graphql::*;
-use tokio::sync::mpsc::Sender;
+use sqlx::PgPool;
 use webgame_collection_api_macros::Error;
 
 use crate::{
-    auth::auth_info::AuthInfo,
-    chat::ChatData,
+    auth::{auth_info::AuthInfo, scope::Scope},
+    chat::{
+        conversation::{get_or_create_direct_conversation, store_message},
+        ChatTransportRef,
+    },
     error::Error,
     schema::types::{
-        chat::Chat,
+        chat::{parse_conversation_id, Chat},
         node::{IdData, IdDataError, NodeIdent},
     },
 };
@@ -18,6 +21,8 @@ use crate::{
 enum ChatMutationError {
     #[error(message = "Invalid target ID")]
     InvalidTargetId(IdDataError),
+    #[error(message = "The target ID is not a user ID")]
+    NotUserId,
 }
 
 #[derive(Default)]
@@ -31,28 +36,60 @@ impl ChatMutation {
         target_id: ID,
         message: String,
     ) -> Result<Chat> {
+        let pool = ctx.data::<PgPool>()?;
+        let auth_info = ctx.data::<AuthInfo>()?;
+        let chat_transport = ctx.data::<ChatTransportRef>()?;
+        let sender_id = auth_info
+            .get_user_id_for(Scope::ChatWrite)
+            .map_err(|e| e.build())?;
+        let target = IdData::try_from(target_id)
+            .map_err(|e| ChatMutationError::InvalidTargetId(e).build())?;
+        if !matches!(target.ty, NodeIdent::User) {
+            return Err(ChatMutationError::NotUserId.build());
+        }
+
+        let conversation_id = get_or_create_direct_conversation(pool, &sender_id, &target.uuid)
+            .await
+            .map_err(|e| e.build())?;
+        // 대상이 구독 중이 아니더라도 나중에 조회할 수 있도록 먼저 저장
+        let chat_data = store_message(pool, &conversation_id, &sender_id, message)
+            .await
+            .map_err(|e| e.build())?;
+        let chat = chat_data.to_chat();
+
+        chat_transport
+            .send(chat_data)
+            .await
+            .map_err(|e| e.build())?;
+
+        Ok(chat)
+    }
+
+    // 멤버로 속한 대화방(DM, 그룹 방, 게임 로비)에 메시지를 전송
+    async fn send_message(
+        &self,
+        ctx: &Context<'_>,
+        conversation_id: ID,
+        message: String,
+    ) -> Result<Chat> {
+        let pool = ctx.data::<PgPool>()?;
         let auth_info = ctx.data::<AuthInfo>()?;
-        let chat_tx = ctx.data::<Sender<ChatData>>()?;
-        let sender_id = auth_info.get_user_id().map_err(|e| e.build())?;
-        let target_uuid = IdData::try_from(target_id)
-            .map_err(|e| ChatMutationError::InvalidTargetId(e).build())?
-            .uuid;
-
-        chat_tx
-            .send(ChatData {
-                sender_id,
-                target_ids: vec![target_uuid],
-                message: message.clone(),
-            })
-            .await?;
-
-        Ok(Chat {
-            sender_id: IdData {
-                ty: NodeIdent::User,
-                uuid: sender_id,
-            }
-            .to_id_scalar(),
-            message: message.clone(),
-        })
+        let chat_transport = ctx.data::<ChatTransportRef>()?;
+        let sender_id = auth_info
+            .get_user_id_for(Scope::ChatWrite)
+            .map_err(|e| e.build())?;
+        let conversation_id = parse_conversation_id(conversation_id).map_err(|e| e.build())?;
+
+        let chat_data = store_message(pool, &conversation_id, &sender_id, message)
+            .await
+            .map_err(|e| e.build())?;
+        let chat = chat_data.to_chat();
+
+        chat_transport
+            .send(chat_data)
+            .await
+            .map_err(|e| e.build())?;
+
+        Ok(chat)
     }
 }
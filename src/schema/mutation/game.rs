@@ -0,0 +1,150 @@
+use std::convert::TryFrom;
+
+use async_graphql::*;
+use sqlx::PgPool;
+use uuid::Uuid;
+use webgame_collection_api_macros::Error;
+
+use crate::{
+    auth::{
+        auth_info::AuthInfo,
+        delegation::{authorize_game, exchange_authorization_code, revoke_game_authorization},
+        scope::Scope,
+    },
+    error::Error,
+    schema::types::{
+        game::{GameAccessToken, Score},
+        node::{IdData, IdDataError, NodeIdent},
+        scalars::DateTimeScalar,
+    },
+};
+
+#[derive(Error)]
+enum GameMutationError {
+    #[error(message = "Database error")]
+    DbError(sqlx::Error),
+    #[error(message = "ID parsing failed")]
+    IdParseError(IdDataError),
+    #[error(message = "The ID is not a game ID")]
+    NotGameId,
+}
+
+#[derive(Default)]
+pub struct GameMutation;
+
+#[Object]
+impl GameMutation {
+    // 임베드된 게임에 넘겨줄 인가 코드를 발급
+    async fn authorize_game(
+        &self,
+        ctx: &Context<'_>,
+        game_id: ID,
+        scopes: Vec<Scope>,
+        code_challenge: String,
+    ) -> Result<String> {
+        let pool = ctx.data::<PgPool>()?;
+        let mut redis_conn = ctx.data::<deadpool_redis::Pool>()?.get().await?;
+        let auth_info = ctx.data::<AuthInfo>()?;
+        let user_id = auth_info.get_user_id().map_err(|e| e.build())?;
+        let game_id = parse_game_id(game_id)?;
+
+        authorize_game(
+            pool,
+            &mut redis_conn,
+            &user_id,
+            auth_info.session_id(),
+            &game_id,
+            scopes,
+            code_challenge,
+        )
+        .await
+        .map_err(|e| e.build())
+    }
+
+    async fn exchange_game_authorization_code(
+        &self,
+        ctx: &Context<'_>,
+        code: String,
+        code_verifier: String,
+    ) -> Result<GameAccessToken> {
+        let pool = ctx.data::<PgPool>()?;
+        let mut redis_conn = ctx.data::<deadpool_redis::Pool>()?.get().await?;
+
+        let token = exchange_authorization_code(pool, &mut redis_conn, &code, &code_verifier)
+            .await
+            .map_err(|e| e.build())?;
+
+        Ok(GameAccessToken {
+            access_token: token.access_token,
+            game_id: IdData {
+                ty: NodeIdent::Game,
+                uuid: token.game_id,
+            }
+            .to_id_scalar(),
+            scopes: token.scopes,
+            expires_at: DateTimeScalar(token.expires_at),
+        })
+    }
+
+    async fn revoke_game_authorization(&self, ctx: &Context<'_>, game_id: ID) -> Result<bool> {
+        let pool = ctx.data::<PgPool>()?;
+        let auth_info = ctx.data::<AuthInfo>()?;
+        let user_id = auth_info.get_user_id().map_err(|e| e.build())?;
+        let game_id = parse_game_id(game_id)?;
+        let mut redis_conn = ctx.data::<deadpool_redis::Pool>()?.get().await?;
+
+        revoke_game_authorization(pool, &mut redis_conn, &user_id, &game_id)
+            .await
+            .map(|_| true)
+            .map_err(|e| e.build())
+    }
+
+    async fn submit_score(&self, ctx: &Context<'_>, game_id: ID, score: i64) -> Result<Score> {
+        let pool = ctx.data::<PgPool>()?;
+        let auth_info = ctx.data::<AuthInfo>()?;
+        let game_id = parse_game_id(game_id)?;
+        let user_id = auth_info
+            .get_user_id_for_game(&game_id, Scope::ScoreWrite)
+            .map_err(|e| e.build())?;
+
+        let record = sqlx::query!(
+            r#"
+            INSERT INTO public.game_score (id, game_id, user_id, score)
+            VALUES ($1, $2, $3, $4)
+            RETURNING id, submitted_at
+            "#,
+            Uuid::new_v4(),
+            game_id,
+            user_id,
+            score,
+        )
+        .fetch_one(pool)
+        .await
+        .map_err(|e| GameMutationError::DbError(e).build())?;
+
+        Ok(Score {
+            id: ID(record.id.to_string()),
+            game_id: IdData {
+                ty: NodeIdent::Game,
+                uuid: game_id,
+            }
+            .to_id_scalar(),
+            user_id: IdData {
+                ty: NodeIdent::User,
+                uuid: user_id,
+            }
+            .to_id_scalar(),
+            score,
+            submitted_at: DateTimeScalar(record.submitted_at),
+        })
+    }
+}
+
+fn parse_game_id(id: ID) -> Result<Uuid> {
+    let id_data = IdData::try_from(id).map_err(|e| GameMutationError::IdParseError(e).build())?;
+
+    match id_data.ty {
+        NodeIdent::Game => Ok(id_data.uuid),
+        _ => Err(GameMutationError::NotGameId.build()),
+    }
+}
@@ -1,7 +1,20 @@
 use async_graphql::*;
 
+pub mod admin;
+pub mod api_key;
 pub mod auth;
 pub mod chat;
+pub mod game;
+pub mod room;
+pub mod user;
 
 #[derive(MergedObject, Default)]
-pub struct MutationRoot(auth::AuthMutation, chat::ChatMutation);
+pub struct MutationRoot(
+    admin::AdminMutation,
+    api_key::ApiKeyMutation,
+    auth::AuthMutation,
+    chat::ChatMutation,
+    game::GameMutation,
+    room::RoomMutation,
+    user::UserMutation,
+);
@@ -0,0 +1,158 @@
+use std::convert::TryFrom;
+
+use async_graphql::*;
+use sqlx::PgPool;
+use uuid::Uuid;
+use webgame_collection_api_macros::Error;
+
+use crate::{
+    auth::{auth_info::AuthInfo, role::Role, scope::Scope},
+    chat::room::{
+        create_room, get_room, invite_to_room, join_room, kick_from_room, leave_room,
+        set_room_member_role, RoomMemberRole,
+    },
+    error::Error,
+    schema::types::{
+        chat::Room,
+        node::{IdData, IdDataError, NodeIdent},
+    },
+};
+
+#[derive(Error)]
+enum RoomMutationError {
+    #[error(message = "Invalid room ID")]
+    InvalidRoomId(IdDataError),
+    #[error(message = "The ID is not a room ID")]
+    NotRoomId,
+    #[error(message = "Invalid user ID")]
+    InvalidUserId(IdDataError),
+    #[error(message = "The ID is not a user ID")]
+    NotUserId,
+}
+
+fn parse_room_id(room_id: ID) -> Result<Uuid> {
+    let id_data =
+        IdData::try_from(room_id).map_err(|e| RoomMutationError::InvalidRoomId(e).build())?;
+    match id_data.ty {
+        NodeIdent::Room => Ok(id_data.uuid),
+        _ => Err(RoomMutationError::NotRoomId.build()),
+    }
+}
+
+fn parse_user_id(user_id: ID) -> Result<Uuid> {
+    let id_data =
+        IdData::try_from(user_id).map_err(|e| RoomMutationError::InvalidUserId(e).build())?;
+    match id_data.ty {
+        NodeIdent::User => Ok(id_data.uuid),
+        _ => Err(RoomMutationError::NotUserId.build()),
+    }
+}
+
+#[derive(Default)]
+pub struct RoomMutation;
+
+#[Object]
+impl RoomMutation {
+    async fn create_room(&self, ctx: &Context<'_>, name: String, is_public: bool) -> Result<Room> {
+        let pool = ctx.data::<PgPool>()?;
+        let auth_info = ctx.data::<AuthInfo>()?;
+        let user_id = auth_info
+            .get_user_id_for(Scope::ChatWrite)
+            .map_err(|e| e.build())?;
+
+        create_room(pool, &user_id, name, is_public)
+            .await
+            .map(Room::from)
+            .map_err(|e| e.build())
+    }
+
+    async fn join_room(&self, ctx: &Context<'_>, room_id: ID) -> Result<Room> {
+        let pool = ctx.data::<PgPool>()?;
+        let auth_info = ctx.data::<AuthInfo>()?;
+        let user_id = auth_info
+            .get_user_id_for(Scope::ChatWrite)
+            .map_err(|e| e.build())?;
+        let room_id = parse_room_id(room_id)?;
+
+        join_room(pool, &room_id, &user_id)
+            .await
+            .map_err(|e| e.build())?;
+
+        get_room(pool, &room_id)
+            .await
+            .map(Room::from)
+            .map_err(|e| e.build())
+    }
+
+    async fn leave_room(&self, ctx: &Context<'_>, room_id: ID) -> Result<bool> {
+        let pool = ctx.data::<PgPool>()?;
+        let auth_info = ctx.data::<AuthInfo>()?;
+        let user_id = auth_info
+            .get_user_id_for(Scope::ChatWrite)
+            .map_err(|e| e.build())?;
+        let room_id = parse_room_id(room_id)?;
+
+        leave_room(pool, &room_id, &user_id)
+            .await
+            .map(|_| true)
+            .map_err(|e| e.build())
+    }
+
+    async fn invite_to_room(&self, ctx: &Context<'_>, room_id: ID, user_id: ID) -> Result<bool> {
+        let pool = ctx.data::<PgPool>()?;
+        let auth_info = ctx.data::<AuthInfo>()?;
+        let inviter_id = auth_info
+            .get_user_id_for(Scope::ChatWrite)
+            .map_err(|e| e.build())?;
+        let room_id = parse_room_id(room_id)?;
+        let user_id = parse_user_id(user_id)?;
+
+        invite_to_room(pool, &room_id, &inviter_id, &user_id)
+            .await
+            .map(|_| true)
+            .map_err(|e| e.build())
+    }
+
+    // 사이트 모더레이터는 방장이 없는 게임 로비를 포함한 모든 방에서 멤버를 내보낼 수 있음
+    async fn kick_from_room(&self, ctx: &Context<'_>, room_id: ID, user_id: ID) -> Result<bool> {
+        let pool = ctx.data::<PgPool>()?;
+        let auth_info = ctx.data::<AuthInfo>()?;
+        let actor_id = auth_info
+            .get_user_id_for(Scope::ChatWrite)
+            .map_err(|e| e.build())?;
+        let room_id = parse_room_id(room_id)?;
+        let user_id = parse_user_id(user_id)?;
+
+        kick_from_room(
+            pool,
+            &room_id,
+            &actor_id,
+            &user_id,
+            auth_info.has_role(Role::Moderator),
+        )
+        .await
+        .map(|_| true)
+        .map_err(|e| e.build())
+    }
+
+    async fn set_room_member_role(
+        &self,
+        ctx: &Context<'_>,
+        room_id: ID,
+        user_id: ID,
+        role: RoomMemberRole,
+    ) -> Result<bool> {
+        let pool = ctx.data::<PgPool>()?;
+        let auth_info = ctx.data::<AuthInfo>()?;
+        let owner_id = auth_info
+            .get_user_id_for(Scope::ChatWrite)
+            .map_err(|e| e.build())?;
+        let room_id = parse_room_id(room_id)?;
+        let user_id = parse_user_id(user_id)?;
+
+        set_room_member_role(pool, &room_id, &owner_id, &user_id, role)
+            .await
+            .map(|_| true)
+            .map_err(|e| e.build())
+    }
+}
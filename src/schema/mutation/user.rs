@@ -0,0 +1,44 @@
+use async_graphql::*;
+use sqlx::PgPool;
+
+use crate::{
+    auth::{
+        auth_info::AuthInfo,
+        profile::{update_profile, ProfileUpdate},
+        verification::send_verification_email,
+    },
+    error::Error,
+    mail::MailerRef,
+    schema::types::user::{UpdateProfileInput, User},
+};
+
+#[derive(Default)]
+pub struct UserMutation;
+
+#[Object]
+impl UserMutation {
+    async fn update_profile(&self, ctx: &Context<'_>, input: UpdateProfileInput) -> Result<User> {
+        let pool = ctx.data::<PgPool>()?;
+        let auth_info = ctx.data::<AuthInfo>()?;
+        let user_id = auth_info.get_user_id().map_err(|e| e.build())?;
+
+        let ProfileUpdate {
+            user,
+            pending_email,
+        } = update_profile(pool, &user_id, input)
+            .await
+            .map_err(|e| e.build())?;
+
+        // 새 주소의 인증을 마치면 이메일이 변경됨
+        if let Some(email) = pending_email {
+            let mailer = ctx.data::<MailerRef>()?;
+            let mut redis_conn = ctx.data::<deadpool_redis::Pool>()?.get().await?;
+
+            send_verification_email(mailer.as_ref(), &mut redis_conn, &user.uuid, &email)
+                .await
+                .map_err(|e| e.build())?;
+        }
+
+        Ok(user)
+    }
+}
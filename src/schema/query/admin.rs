@@ -0,0 +1,55 @@
+use std::convert::TryFrom;
+
+use async_graphql::{connection::Connection, *};
+use sqlx::PgPool;
+use uuid::Uuid;
+use webgame_collection_api_macros::Error;
+
+use crate::{
+    auth::role::{Role, RoleGuard},
+    error::Error,
+    schema::types::{
+        node::{IdData, IdDataError, NodeIdent},
+        scalars::DateTimeScalar,
+        security_event::{security_event_connection, SecurityEvent},
+    },
+};
+
+#[derive(Error)]
+enum AdminQueryError {
+    #[error(message = "ID parsing failed")]
+    IdParseError(IdDataError),
+    #[error(message = "The ID is not a user ID")]
+    NotUserId,
+}
+
+#[derive(Default)]
+pub struct AdminQuery;
+
+#[Object]
+impl AdminQuery {
+    #[graphql(guard(RoleGuard(role = "Role::Admin")))]
+    async fn security_events(
+        &self,
+        ctx: &Context<'_>,
+        user_id: Option<ID>,
+        after: Option<String>,
+        before: Option<String>,
+        first: Option<i32>,
+        last: Option<i32>,
+    ) -> Result<Connection<DateTimeScalar, SecurityEvent>> {
+        let pool = ctx.data::<PgPool>()?;
+        let user_id = user_id.map(parse_user_id).transpose()?;
+
+        security_event_connection(pool, user_id, after, before, first, last).await
+    }
+}
+
+fn parse_user_id(id: ID) -> Result<Uuid> {
+    let id_data = IdData::try_from(id).map_err(|e| AdminQueryError::IdParseError(e).build())?;
+
+    match id_data.ty {
+        NodeIdent::User => Ok(id_data.uuid),
+        _ => Err(AdminQueryError::NotUserId.build()),
+    }
+}
@@ -0,0 +1,236 @@
+use async_graphql::{connection::*, *};
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+
+use crate::{
+    auth::{auth_info::AuthInfo, scope::Scope},
+    chat::{
+        conversation::{is_member, ConversationError},
+        room::ConversationType,
+    },
+    error::Error,
+    schema::types::{
+        chat::{parse_conversation_id, Chat, Conversation},
+        node::{IdData, NodeIdent},
+        scalars::{DateTimeScalar, KeysetCursor},
+    },
+};
+
+#[derive(Default)]
+pub struct ChatQuery;
+
+#[Object]
+impl ChatQuery {
+    async fn conversations(
+        &self,
+        ctx: &Context<'_>,
+        after: Option<String>,
+        before: Option<String>,
+        first: Option<i32>,
+        last: Option<i32>,
+    ) -> Result<Connection<KeysetCursor, Conversation>> {
+        let pool = ctx.data::<PgPool>()?;
+        let auth_info = ctx.data::<AuthInfo>()?;
+        let user_id = auth_info
+            .get_user_id_for(Scope::ChatRead)
+            .map_err(|e| e.build())?;
+
+        query(
+            after,
+            before,
+            first,
+            last,
+            |after: Option<KeysetCursor>, before: Option<KeysetCursor>, first, last| async move {
+                let rows = sqlx::query!(
+                    r#"
+                    SELECT
+                        c.id,
+                        c.type AS "ty: ConversationType",
+                        c.name,
+                        c.created_at,
+                        c.last_message_at,
+                        ARRAY(
+                            SELECT user_id FROM public.conversation_member
+                            WHERE conversation_id = c.id
+                        ) AS "member_ids!",
+                        after_at AS "after_at: DateTime<Utc>",
+                        before_at AS "before_at: DateTime<Utc>"
+                    FROM
+                        public.conversation c
+                        JOIN public.conversation_member m ON m.conversation_id = c.id,
+                        (VALUES ($1::TIMESTAMPTZ, $2::UUID, $3::TIMESTAMPTZ, $4::UUID))
+                            s(after_at, after_id, before_at, before_id)
+                    WHERE
+                        m.user_id = $7 AND
+                        (after_at IS NULL OR (c.last_message_at, c.id) > (after_at, after_id)) AND
+                        (before_at IS NULL OR (c.last_message_at, c.id) < (before_at, before_id))
+                    ORDER BY
+                        (CASE WHEN $5 THEN c.last_message_at END) DESC,
+                        (CASE WHEN $5 THEN c.id END) DESC,
+                        c.last_message_at ASC,
+                        c.id ASC
+                    LIMIT $6 + 1
+                    "#,
+                    after.as_ref().map(|cursor| cursor.timestamp),
+                    after.as_ref().map(|cursor| cursor.id),
+                    before.as_ref().map(|cursor| cursor.timestamp),
+                    before.as_ref().map(|cursor| cursor.id),
+                    last.is_some(),
+                    first.or(last).unwrap_or(10) as i32,
+                    user_id,
+                )
+                .fetch_all(pool)
+                .await?;
+
+                let mut connection = Connection::new(
+                    first.is_none() && rows.len() > last.unwrap_or(10),
+                    last.is_none() && rows.len() > first.unwrap_or(10),
+                );
+                let iter = rows.into_iter().map(|row| {
+                    Edge::new(
+                        KeysetCursor {
+                            timestamp: row.last_message_at,
+                            id: row.id,
+                        },
+                        Conversation {
+                            id: IdData {
+                                ty: NodeIdent::Conversation,
+                                uuid: row.id,
+                            }
+                            .to_id_scalar(),
+                            ty: row.ty,
+                            name: row.name,
+                            member_ids: row
+                                .member_ids
+                                .into_iter()
+                                .map(|uuid| {
+                                    IdData {
+                                        ty: NodeIdent::User,
+                                        uuid,
+                                    }
+                                    .to_id_scalar()
+                                })
+                                .collect(),
+                            created_at: DateTimeScalar(row.created_at),
+                            last_message_at: DateTimeScalar(row.last_message_at),
+                        },
+                    )
+                });
+                if last.is_some() {
+                    connection.append(iter.take(last.unwrap_or(10)).rev());
+                } else {
+                    connection.append(iter.take(first.unwrap_or(10)));
+                }
+                Ok(connection)
+            },
+        )
+        .await
+    }
+
+    async fn messages(
+        &self,
+        ctx: &Context<'_>,
+        conversation_id: ID,
+        after: Option<String>,
+        before: Option<String>,
+        first: Option<i32>,
+        last: Option<i32>,
+    ) -> Result<Connection<KeysetCursor, Chat>> {
+        let pool = ctx.data::<PgPool>()?;
+        let auth_info = ctx.data::<AuthInfo>()?;
+        let user_id = auth_info
+            .get_user_id_for(Scope::ChatRead)
+            .map_err(|e| e.build())?;
+        let conversation_id = parse_conversation_id(conversation_id).map_err(|e| e.build())?;
+
+        let member = is_member(pool, &conversation_id, &user_id)
+            .await
+            .map_err(|e| e.build())?;
+        if !member {
+            return Err(ConversationError::NotMember.build());
+        }
+
+        query(
+            after,
+            before,
+            first,
+            last,
+            |after: Option<KeysetCursor>, before: Option<KeysetCursor>, first, last| async move {
+                let rows = sqlx::query!(
+                    r#"
+                    SELECT
+                        id,
+                        sender_id,
+                        message,
+                        created_at,
+                        after_at AS "after_at: DateTime<Utc>",
+                        before_at AS "before_at: DateTime<Utc>"
+                    FROM
+                        public.chat_message,
+                        (VALUES ($1::TIMESTAMPTZ, $2::UUID, $3::TIMESTAMPTZ, $4::UUID))
+                            s(after_at, after_id, before_at, before_id)
+                    WHERE
+                        conversation_id = $7 AND
+                        (after_at IS NULL OR (created_at, id) > (after_at, after_id)) AND
+                        (before_at IS NULL OR (created_at, id) < (before_at, before_id))
+                    ORDER BY
+                        (CASE WHEN $5 THEN created_at END) DESC,
+                        (CASE WHEN $5 THEN id END) DESC,
+                        created_at ASC,
+                        id ASC
+                    LIMIT $6 + 1
+                    "#,
+                    after.as_ref().map(|cursor| cursor.timestamp),
+                    after.as_ref().map(|cursor| cursor.id),
+                    before.as_ref().map(|cursor| cursor.timestamp),
+                    before.as_ref().map(|cursor| cursor.id),
+                    last.is_some(),
+                    first.or(last).unwrap_or(10) as i32,
+                    conversation_id,
+                )
+                .fetch_all(pool)
+                .await?;
+
+                let mut connection = Connection::new(
+                    first.is_none() && rows.len() > last.unwrap_or(10),
+                    last.is_none() && rows.len() > first.unwrap_or(10),
+                );
+                let iter = rows.into_iter().map(|row| {
+                    Edge::new(
+                        KeysetCursor {
+                            timestamp: row.created_at,
+                            id: row.id,
+                        },
+                        Chat {
+                            id: IdData {
+                                ty: NodeIdent::Chat,
+                                uuid: row.id,
+                            }
+                            .to_id_scalar(),
+                            conversation_id: IdData {
+                                ty: NodeIdent::Conversation,
+                                uuid: conversation_id,
+                            }
+                            .to_id_scalar(),
+                            sender_id: IdData {
+                                ty: NodeIdent::User,
+                                uuid: row.sender_id,
+                            }
+                            .to_id_scalar(),
+                            message: row.message,
+                            created_at: DateTimeScalar(row.created_at),
+                            conversation_uuid: conversation_id,
+                        },
+                    )
+                });
+                if last.is_some() {
+                    connection.append(iter.take(last.unwrap_or(10)).rev());
+                } else {
+                    connection.append(iter.take(first.unwrap_or(10)));
+                }
+                Ok(connection)
+            },
+        )
+        .await
+    }
+}
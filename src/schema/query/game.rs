@@ -80,6 +80,7 @@ impl GameQuery {
                             min_players: row.min_players,
                             max_players: row.max_players,
                             description: row.description,
+                            uuid: row.id,
                         },
                     )
                 });
@@ -1,8 +1,16 @@
 use async_graphql::*;
 
+mod admin;
+mod chat;
 mod game;
 mod node;
 mod user;
 
 #[derive(MergedObject, Default)]
-pub struct QueryRoot(node::NodeQuery, game::GameQuery, user::UserQuery);
+pub struct QueryRoot(
+    admin::AdminQuery,
+    chat::ChatQuery,
+    node::NodeQuery,
+    game::GameQuery,
+    user::UserQuery,
+);
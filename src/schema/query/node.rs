@@ -28,7 +28,7 @@ impl NodeQuery {
 
         let node = id_data
             .ty
-            .resolve(&id_data.uuid, &pool)
+            .resolve(ctx, &id_data.uuid, &pool)
             .await
             .ok_or(NodeQueryError::ResolverNotFound.build())?;
 
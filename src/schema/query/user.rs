@@ -3,7 +3,7 @@ use sqlx::PgPool;
 use webgame_collection_api_macros::Error;
 
 use crate::{
-    auth::auth_info::AuthInfo,
+    auth::{auth_info::AuthInfo, scope::Scope},
     error::Error,
     schema::types::{
         node::{IdData, NodeIdent},
@@ -28,12 +28,14 @@ impl UserQuery {
     async fn me(&self, ctx: &Context<'_>) -> Result<User> {
         let pool = ctx.data::<PgPool>()?;
         let auth_info = ctx.data::<AuthInfo>()?;
-        let user_id = auth_info.get_user_id().map_err(|e| e.build())?;
+        let user_id = auth_info
+            .get_user_id_for(Scope::ProfileRead)
+            .map_err(|e| e.build())?;
 
         let user = sqlx::query!(
             r#"
             SELECT * FROM public.user
-            WHERE id = $1
+            WHERE id = $1 AND deleted_at IS NULL
             "#,
             user_id,
         )
@@ -48,8 +50,13 @@ impl UserQuery {
                 uuid: user.id,
             }
             .to_id_scalar(),
+            uuid: user.id,
             nickname: user.nickname,
             email: user.email,
+            email_verified: user.email_verified_at.is_some(),
+            avatar_url: user.avatar_url,
+            bio: user.bio,
+            is_guest: user.is_guest,
             registered_at: DateTimeScalar(user.registered_at),
             deleted_at: user.deleted_at.map(DateTimeScalar),
         })
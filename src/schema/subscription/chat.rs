@@ -1,10 +1,11 @@
 use async_graphql::*;
-use futures::Stream;
-use tokio::sync::mpsc;
-use tokio_stream::wrappers::ReceiverStream;
+use futures::{future, Stream, StreamExt};
 
 use crate::{
-    auth::auth_info::AuthInfo, chat::CHANNEL_MAP, error::Error, schema::types::chat::Chat,
+    auth::{auth_info::AuthInfo, connection_auth::ConnectionAuth, scope::Scope},
+    chat::subscribe,
+    error::Error,
+    schema::types::chat::{parse_conversation_id, Chat},
 };
 
 #[derive(Default)]
@@ -12,14 +13,28 @@ pub struct ChatSubscription;
 
 #[Subscription]
 impl ChatSubscription {
-    async fn chats(&self, ctx: &Context<'_>) -> Result<impl Stream<Item = Chat>> {
+    async fn chats(
+        &self,
+        ctx: &Context<'_>,
+        conversation_id: Option<ID>,
+    ) -> Result<impl Stream<Item = Chat>> {
         let auth_info = ctx.data::<AuthInfo>()?;
-        let user_id = auth_info.get_user_id().map_err(|e| e.build())?;
-        let (tx, rx) = mpsc::channel::<Chat>(10);
+        let user_id = auth_info
+            .get_user_id_for(Scope::ChatRead)
+            .map_err(|e| e.build())?;
+        let conversation_id = conversation_id
+            .map(parse_conversation_id)
+            .transpose()
+            .map_err(|e| e.build())?;
+        // 연결의 토큰을 교체할 때도 이 구독에 필요한 범위가 유지되어야 함
+        if let Ok(connection_auth) = ctx.data::<ConnectionAuth>() {
+            connection_auth.require(Scope::ChatRead).await;
+        }
+        let subscription = subscribe(user_id);
 
-        let mut map_guard = CHANNEL_MAP.lock().await;
-        map_guard.insert(user_id, tx);
-
-        Ok(ReceiverStream::new(rx))
+        // 대화 ID가 주어지면 해당 대화의 메시지만 전달
+        Ok(subscription.filter(move |chat| {
+            future::ready(conversation_id.map_or(true, |id| id == chat.conversation_uuid))
+        }))
     }
 }
@@ -1,7 +1,138 @@
+use std::convert::TryFrom;
+
 use async_graphql::*;
+use sqlx::PgPool;
+use uuid::Uuid;
+use webgame_collection_api_macros::Error;
+
+use super::{
+    node::{IdData, IdDataError, NodeIdent},
+    scalars::DateTimeScalar,
+};
+use crate::{
+    auth::{auth_info::AuthInfo, scope::Scope},
+    chat::{
+        conversation::{is_member, ConversationError},
+        room::{get_room_members, ConversationType, RoomData, RoomMemberRole},
+    },
+    error::Error,
+};
 
 #[derive(SimpleObject)]
 pub struct Chat {
+    pub id: ID,
+    pub conversation_id: ID,
     pub sender_id: ID,
     pub message: String,
+    pub created_at: DateTimeScalar,
+    #[graphql(skip)]
+    pub conversation_uuid: Uuid,
+}
+
+#[derive(Error)]
+pub enum ConversationIdError {
+    #[error(message = "Invalid conversation ID")]
+    InvalidId(IdDataError),
+    #[error(message = "The ID is not a conversation ID")]
+    NotConversationId,
+}
+
+// 방도 대화의 한 종류이므로 방 ID도 대화 ID로 받음
+pub fn parse_conversation_id(id: ID) -> Result<Uuid, ConversationIdError> {
+    let id_data = IdData::try_from(id).map_err(ConversationIdError::InvalidId)?;
+    match id_data.ty {
+        NodeIdent::Conversation | NodeIdent::Room => Ok(id_data.uuid),
+        _ => Err(ConversationIdError::NotConversationId),
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct Conversation {
+    pub id: ID,
+    #[graphql(name = "type")]
+    pub ty: ConversationType,
+    pub name: Option<String>,
+    pub member_ids: Vec<ID>,
+    pub created_at: DateTimeScalar,
+    pub last_message_at: DateTimeScalar,
+}
+
+#[derive(SimpleObject)]
+#[graphql(complex)]
+pub struct Room {
+    pub id: ID,
+    pub name: Option<String>,
+    pub is_public: bool,
+    pub game_id: Option<ID>,
+    pub created_at: DateTimeScalar,
+    #[graphql(skip)]
+    pub uuid: Uuid,
+}
+
+impl From<RoomData> for Room {
+    fn from(room: RoomData) -> Self {
+        Room {
+            id: IdData {
+                ty: NodeIdent::Room,
+                uuid: room.id,
+            }
+            .to_id_scalar(),
+            name: room.name,
+            is_public: room.is_public,
+            game_id: room.game_id.map(|uuid| {
+                IdData {
+                    ty: NodeIdent::Game,
+                    uuid,
+                }
+                .to_id_scalar()
+            }),
+            created_at: DateTimeScalar(room.created_at),
+            uuid: room.id,
+        }
+    }
+}
+
+#[ComplexObject]
+impl Room {
+    // 비공개 방의 멤버 목록은 멤버만 조회할 수 있음
+    async fn members(&self, ctx: &Context<'_>) -> Result<Vec<RoomMember>> {
+        let pool = ctx.data::<PgPool>()?;
+
+        if !self.is_public {
+            let auth_info = ctx.data::<AuthInfo>()?;
+            let user_id = auth_info
+                .get_user_id_for(Scope::ChatRead)
+                .map_err(|e| e.build())?;
+            let member = is_member(pool, &self.uuid, &user_id)
+                .await
+                .map_err(|e| e.build())?;
+            if !member {
+                return Err(ConversationError::NotMember.build());
+            }
+        }
+
+        let members = get_room_members(pool, &self.uuid)
+            .await
+            .map_err(|e| e.build())?;
+
+        Ok(members
+            .into_iter()
+            .map(|member| RoomMember {
+                user_id: IdData {
+                    ty: NodeIdent::User,
+                    uuid: member.user_id,
+                }
+                .to_id_scalar(),
+                role: member.role,
+                joined_at: DateTimeScalar(member.joined_at),
+            })
+            .collect())
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct RoomMember {
+    pub user_id: ID,
+    pub role: RoomMemberRole,
+    pub joined_at: DateTimeScalar,
 }
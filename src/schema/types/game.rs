@@ -1,11 +1,46 @@
-use super::localized_string::LocalizedString;
+use super::{chat::Room, localized_string::LocalizedString, scalars::DateTimeScalar};
+use crate::{auth::scope::Scope, chat::room::get_lobby, error::Error};
 use async_graphql::*;
+use sqlx::PgPool;
+use uuid::Uuid;
 
 #[derive(SimpleObject)]
+#[graphql(complex)]
 pub struct Game {
     pub id: ID,
     pub name: LocalizedString,
     pub min_players: i16,
     pub max_players: i16,
     pub description: LocalizedString,
+    #[graphql(skip)]
+    pub uuid: Uuid,
+}
+
+#[ComplexObject]
+impl Game {
+    async fn lobby(&self, ctx: &Context<'_>) -> Result<Room> {
+        let pool = ctx.data::<PgPool>()?;
+
+        get_lobby(pool, &self.uuid)
+            .await
+            .map(Room::from)
+            .map_err(|e| e.build())
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct GameAccessToken {
+    pub access_token: String,
+    pub game_id: ID,
+    pub scopes: Vec<Scope>,
+    pub expires_at: DateTimeScalar,
+}
+
+#[derive(SimpleObject)]
+pub struct Score {
+    pub id: ID,
+    pub game_id: ID,
+    pub user_id: ID,
+    pub score: i64,
+    pub submitted_at: DateTimeScalar,
 }
@@ -4,4 +4,5 @@ pub mod localized_string;
 pub mod node;
 mod resolvers;
 pub mod scalars;
+pub mod security_event;
 pub mod user;
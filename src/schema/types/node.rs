@@ -5,8 +5,13 @@ use uuid::Uuid;
 use webgame_collection_api_macros::GenNodeIdent;
 
 use super::{
+    chat::{Chat, Conversation, Room},
     game::Game,
-    resolvers::{game::game_resolver, user::user_resolver},
+    resolvers::{
+        chat::{chat_resolver, conversation_resolver, room_resolver},
+        game::game_resolver,
+        user::user_resolver,
+    },
     user::User,
 };
 
@@ -17,6 +22,12 @@ pub enum Node {
     User(User),
     #[node_ident(resolver = "game_resolver")]
     Game(Game),
+    #[node_ident(resolver = "chat_resolver")]
+    Chat(Chat),
+    #[node_ident(resolver = "conversation_resolver")]
+    Conversation(Conversation),
+    #[node_ident(resolver = "room_resolver")]
+    Room(Room),
 }
 
 pub struct IdData {
@@ -0,0 +1,132 @@
+use async_graphql::Context;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{
+    auth::{auth_info::AuthInfo, scope::Scope},
+    chat::{
+        conversation::is_member,
+        room::{get_room, ConversationType},
+    },
+    schema::types::{
+        chat::{Chat, Conversation, Room},
+        node::{IdData, Node, NodeIdent},
+        scalars::DateTimeScalar,
+    },
+};
+
+// 대화에 참여 중인 유저만 대화와 메시지를 조회할 수 있음
+async fn is_caller_member(ctx: &Context<'_>, pool: &PgPool, conversation_id: &Uuid) -> bool {
+    let user_id = match ctx
+        .data_opt::<AuthInfo>()
+        .and_then(|auth_info| auth_info.get_user_id_for(Scope::ChatRead).ok())
+    {
+        Some(user_id) => user_id,
+        None => return false,
+    };
+
+    is_member(pool, conversation_id, &user_id)
+        .await
+        .unwrap_or(false)
+}
+
+pub async fn chat_resolver(ctx: &Context<'_>, uuid: &Uuid, pool: &PgPool) -> Option<Node> {
+    let chat = sqlx::query!(
+        r#"
+        SELECT * FROM public.chat_message
+        WHERE id = $1
+        "#,
+        uuid,
+    )
+    .fetch_optional(pool)
+    .await
+    .ok()
+    .flatten()?;
+
+    if !is_caller_member(ctx, pool, &chat.conversation_id).await {
+        return None;
+    }
+
+    Some(Node::Chat(Chat {
+        id: IdData {
+            ty: NodeIdent::Chat,
+            uuid: chat.id,
+        }
+        .to_id_scalar(),
+        conversation_id: IdData {
+            ty: NodeIdent::Conversation,
+            uuid: chat.conversation_id,
+        }
+        .to_id_scalar(),
+        sender_id: IdData {
+            ty: NodeIdent::User,
+            uuid: chat.sender_id,
+        }
+        .to_id_scalar(),
+        message: chat.message,
+        created_at: DateTimeScalar(chat.created_at),
+        conversation_uuid: chat.conversation_id,
+    }))
+}
+
+pub async fn conversation_resolver(ctx: &Context<'_>, uuid: &Uuid, pool: &PgPool) -> Option<Node> {
+    if !is_caller_member(ctx, pool, uuid).await {
+        return None;
+    }
+
+    let conversation = sqlx::query!(
+        r#"
+        SELECT
+            c.id,
+            c.type AS "ty: ConversationType",
+            c.name,
+            c.created_at,
+            c.last_message_at,
+            ARRAY(
+                SELECT user_id FROM public.conversation_member
+                WHERE conversation_id = c.id
+            ) AS "member_ids!"
+        FROM public.conversation c
+        WHERE c.id = $1
+        "#,
+        uuid,
+    )
+    .fetch_optional(pool)
+    .await
+    .ok()
+    .flatten()?;
+
+    Some(Node::Conversation(Conversation {
+        id: IdData {
+            ty: NodeIdent::Conversation,
+            uuid: conversation.id,
+        }
+        .to_id_scalar(),
+        ty: conversation.ty,
+        name: conversation.name,
+        member_ids: conversation
+            .member_ids
+            .into_iter()
+            .map(|uuid| {
+                IdData {
+                    ty: NodeIdent::User,
+                    uuid,
+                }
+                .to_id_scalar()
+            })
+            .collect(),
+        created_at: DateTimeScalar(conversation.created_at),
+        last_message_at: DateTimeScalar(conversation.last_message_at),
+    }))
+}
+
+// 비공개 방은 멤버에게만 노출
+pub async fn room_resolver(ctx: &Context<'_>, uuid: &Uuid, pool: &PgPool) -> Option<Node> {
+    let room = get_room(pool, uuid).await.ok()?;
+
+    if !room.is_public && !is_caller_member(ctx, pool, uuid).await {
+        return None;
+    }
+
+    Some(Node::Room(Room::from(room)))
+}
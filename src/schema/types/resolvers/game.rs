@@ -1,3 +1,4 @@
+use async_graphql::Context;
 use sqlx::PgPool;
 use uuid::Uuid;
 
@@ -7,7 +8,7 @@ use crate::schema::types::{
     node::{IdData, Node, NodeIdent},
 };
 
-pub async fn game_resolver(uuid: &Uuid, pool: &PgPool) -> Option<Node> {
+pub async fn game_resolver(_ctx: &Context<'_>, uuid: &Uuid, pool: &PgPool) -> Option<Node> {
     let game = sqlx::query!(
         r#"
         SELECT
@@ -37,5 +38,6 @@ pub async fn game_resolver(uuid: &Uuid, pool: &PgPool) -> Option<Node> {
         min_players: game.min_players,
         max_players: game.max_players,
         description: game.description,
+        uuid: game.id,
     }))
 }
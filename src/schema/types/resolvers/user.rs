@@ -1,3 +1,4 @@
+use async_graphql::Context;
 use sqlx::PgPool;
 use uuid::Uuid;
 
@@ -7,11 +8,11 @@ use crate::schema::types::{
     user::User,
 };
 
-pub async fn user_resolver(uuid: &Uuid, pool: &PgPool) -> Option<Node> {
+pub async fn user_resolver(_ctx: &Context<'_>, uuid: &Uuid, pool: &PgPool) -> Option<Node> {
     let user = sqlx::query!(
         r#"
         SELECT * FROM public.user
-        WHERE id = $1
+        WHERE id = $1 AND deleted_at IS NULL
         "#,
         uuid,
     )
@@ -26,8 +27,13 @@ pub async fn user_resolver(uuid: &Uuid, pool: &PgPool) -> Option<Node> {
             uuid: user.id,
         }
         .to_id_scalar(),
+        uuid: user.id,
         nickname: user.nickname,
         email: user.email,
+        email_verified: user.email_verified_at.is_some(),
+        avatar_url: user.avatar_url,
+        bio: user.bio,
+        is_guest: user.is_guest,
         registered_at: DateTimeScalar(user.registered_at),
         deleted_at: user.deleted_at.map(DateTimeScalar),
     }))
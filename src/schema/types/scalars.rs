@@ -2,6 +2,7 @@ use std::{fmt::Display, str};
 
 use async_graphql::{connection::CursorType, *};
 use chrono::{DateTime, Utc};
+use uuid::Uuid;
 
 pub struct DateTimeScalar(pub DateTime<Utc>);
 
@@ -52,3 +53,46 @@ impl Display for DateTimeCursorDecodeError {
         f.write_str(&format!("{:?}", self))
     }
 }
+
+// 시각이 같은 항목이 여러 개여도 순서가 고정되도록 ID를 함께 사용하는 커서
+pub struct KeysetCursor {
+    pub timestamp: DateTime<Utc>,
+    pub id: Uuid,
+}
+
+impl CursorType for KeysetCursor {
+    type Error = KeysetCursorDecodeError;
+
+    fn decode_cursor(s: &str) -> Result<Self, Self::Error> {
+        let vec = base64::decode(s).map_err(KeysetCursorDecodeError::Base64)?;
+        let s = str::from_utf8(vec.as_slice()).map_err(KeysetCursorDecodeError::Utf8)?;
+        let (id, timestamp) = s
+            .split_once(':')
+            .ok_or(KeysetCursorDecodeError::InvalidFormat)?;
+        Ok(KeysetCursor {
+            timestamp: DateTime::parse_from_rfc3339(timestamp)
+                .map_err(KeysetCursorDecodeError::DateTime)?
+                .with_timezone(&Utc),
+            id: Uuid::parse_str(id).map_err(KeysetCursorDecodeError::Uuid)?,
+        })
+    }
+
+    fn encode_cursor(&self) -> String {
+        base64::encode(format!("{}:{}", self.id, self.timestamp.to_rfc3339()))
+    }
+}
+
+#[derive(Debug)]
+pub enum KeysetCursorDecodeError {
+    Base64(base64::DecodeError),
+    Utf8(str::Utf8Error),
+    InvalidFormat,
+    DateTime(chrono::ParseError),
+    Uuid(uuid::Error),
+}
+
+impl Display for KeysetCursorDecodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&format!("{:?}", self))
+    }
+}
@@ -0,0 +1,114 @@
+use async_graphql::{connection::*, *};
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use super::{
+    node::{IdData, NodeIdent},
+    scalars::DateTimeScalar,
+};
+use crate::auth::audit::{SecurityEventOutcome, SecurityEventType};
+
+#[derive(SimpleObject)]
+pub struct SecurityEvent {
+    pub id: ID,
+    pub user_id: Option<ID>,
+    #[graphql(name = "type")]
+    pub ty: SecurityEventType,
+    pub outcome: SecurityEventOutcome,
+    pub identifier: Option<String>,
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+    pub detail: Option<String>,
+    pub created_at: DateTimeScalar,
+}
+
+// user_id가 None이면 모든 유저의 이벤트를 조회
+pub async fn security_event_connection(
+    pool: &PgPool,
+    user_id: Option<Uuid>,
+    after: Option<String>,
+    before: Option<String>,
+    first: Option<i32>,
+    last: Option<i32>,
+) -> Result<Connection<DateTimeScalar, SecurityEvent>> {
+    query(
+        after,
+        before,
+        first,
+        last,
+        |after: Option<DateTimeScalar>, before, first, last| async move {
+            let after = after.map(|scalar| scalar.0);
+            let before = before.map(|scalar| scalar.0);
+
+            let rows = sqlx::query!(
+                r#"
+                SELECT
+                    id,
+                    user_id,
+                    type AS "ty: SecurityEventType",
+                    outcome AS "outcome: SecurityEventOutcome",
+                    identifier,
+                    ip,
+                    user_agent,
+                    detail,
+                    created_at,
+                    after AS "after: DateTime<Utc>",
+                    before AS "before: DateTime<Utc>"
+                FROM
+                    public.security_event,
+                    (VALUES ($1::TIMESTAMPTZ, $2::TIMESTAMPTZ)) s(after, before)
+                WHERE
+                    ($5::UUID IS NULL OR user_id = $5) AND
+                    (after IS NULL OR created_at > after) AND
+                    (before IS NULL OR created_at < before)
+                ORDER BY
+                    (CASE WHEN $3 THEN created_at END) DESC,
+                    created_at ASC
+                LIMIT $4 + 1
+                "#,
+                after,
+                before,
+                last.is_some(),
+                first.or(last).unwrap_or(10) as i32,
+                user_id,
+            )
+            .fetch_all(pool)
+            .await?;
+
+            let mut connection = Connection::new(
+                first.is_none() && rows.len() > last.unwrap_or(10),
+                last.is_none() && rows.len() > first.unwrap_or(10),
+            );
+            let iter = rows.into_iter().map(|row| {
+                Edge::new(
+                    DateTimeScalar(row.created_at),
+                    SecurityEvent {
+                        id: ID(row.id.to_string()),
+                        user_id: row.user_id.map(|uuid| {
+                            IdData {
+                                ty: NodeIdent::User,
+                                uuid,
+                            }
+                            .to_id_scalar()
+                        }),
+                        ty: row.ty,
+                        outcome: row.outcome,
+                        identifier: row.identifier,
+                        ip: row.ip,
+                        user_agent: row.user_agent,
+                        detail: row.detail,
+                        created_at: DateTimeScalar(row.created_at),
+                    },
+                )
+            });
+            if last.is_some() {
+                connection.append(iter.take(last.unwrap_or(10)).rev());
+            } else {
+                connection.append(iter.take(first.unwrap_or(10)));
+            }
+            Ok(connection)
+        },
+    )
+    .await
+}
@@ -1,16 +1,195 @@
-use super::scalars::DateTimeScalar;
+use super::{
+    scalars::DateTimeScalar,
+    security_event::{security_event_connection, SecurityEvent},
+};
 use async_graphql::validators::Email;
-use async_graphql::*;
+use async_graphql::{connection::Connection, *};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{
+    auth::{
+        api_key::{list_api_keys, ApiKeyData},
+        auth_info::AuthInfo,
+        link::get_auth_methods,
+        scope::Scope,
+        session::list_sessions,
+        AuthMethodType,
+    },
+    error::Error,
+};
 
 #[derive(SimpleObject)]
+#[graphql(complex)]
 pub struct User {
     pub id: ID,
+    #[graphql(skip)]
+    pub uuid: Uuid,
     pub nickname: String,
+    #[graphql(skip)]
     pub email: String,
+    #[graphql(skip)]
+    pub email_verified: bool,
+    pub avatar_url: Option<String>,
+    pub bio: Option<String>,
+    pub is_guest: bool,
     pub registered_at: DateTimeScalar,
+    #[graphql(skip)]
     pub deleted_at: Option<DateTimeScalar>,
 }
 
+#[ComplexObject]
+impl User {
+    async fn email(&self, ctx: &Context<'_>) -> Result<Option<String>> {
+        // 게스트 계정의 이메일은 임의로 생성된 값이므로 노출하지 않음
+        Ok(self
+            .can_view_private_fields(ctx)?
+            .then(|| self.email.clone())
+            .filter(|_| !self.is_guest))
+    }
+
+    async fn email_verified(&self, ctx: &Context<'_>) -> Result<Option<bool>> {
+        Ok(self
+            .can_view_private_fields(ctx)?
+            .then(|| self.email_verified))
+    }
+
+    async fn deleted_at(&self, ctx: &Context<'_>) -> Result<Option<DateTimeScalar>> {
+        Ok(self
+            .can_view_private_fields(ctx)?
+            .then(|| self.deleted_at.as_ref().map(|d| DateTimeScalar(d.0)))
+            .flatten())
+    }
+
+    async fn auth_methods(&self, ctx: &Context<'_>) -> Result<Vec<AuthMethod>> {
+        let pool = ctx.data::<PgPool>()?;
+        let auth_info = ctx.data::<AuthInfo>()?;
+        auth_info
+            .check_owner_or_admin(&self.uuid)
+            .map_err(|e| e.build())?;
+
+        get_auth_methods(pool, &self.uuid)
+            .await
+            .map_err(|e| e.build())
+    }
+
+    async fn sessions(&self, ctx: &Context<'_>) -> Result<Vec<Session>> {
+        let auth_info = ctx.data::<AuthInfo>()?;
+        let mut redis_conn = ctx.data::<deadpool_redis::Pool>()?.get().await?;
+        auth_info
+            .check_owner_or_admin(&self.uuid)
+            .map_err(|e| e.build())?;
+
+        let sessions = list_sessions(&self.uuid, &mut redis_conn)
+            .await
+            .map_err(|e| e.build())?;
+
+        Ok(sessions
+            .into_iter()
+            .map(|session| Session {
+                id: ID(session.id.to_string()),
+                ip: session.ip,
+                user_agent: session.user_agent,
+                created_at: DateTimeScalar(session.created_at),
+                last_refreshed_at: DateTimeScalar(session.last_refreshed_at),
+                current: auth_info.session_id() == Some(session.id),
+            })
+            .collect())
+    }
+
+    async fn api_keys(&self, ctx: &Context<'_>) -> Result<Vec<ApiKey>> {
+        let pool = ctx.data::<PgPool>()?;
+        let auth_info = ctx.data::<AuthInfo>()?;
+        auth_info.check_owner(&self.uuid).map_err(|e| e.build())?;
+
+        let keys = list_api_keys(pool, &self.uuid)
+            .await
+            .map_err(|e| e.build())?;
+
+        Ok(keys.into_iter().map(ApiKey::from).collect())
+    }
+
+    async fn security_events(
+        &self,
+        ctx: &Context<'_>,
+        after: Option<String>,
+        before: Option<String>,
+        first: Option<i32>,
+        last: Option<i32>,
+    ) -> Result<Connection<DateTimeScalar, SecurityEvent>> {
+        let pool = ctx.data::<PgPool>()?;
+        let auth_info = ctx.data::<AuthInfo>()?;
+        auth_info
+            .check_owner_or_admin(&self.uuid)
+            .map_err(|e| e.build())?;
+
+        security_event_connection(pool, Some(self.uuid), after, before, first, last).await
+    }
+}
+
+impl User {
+    // 본인이나 관리자가 아닌 경우 공개되지 않는 필드는 null로 응답
+    // 게임에 위임된 토큰은 profile:read 범위가 있더라도 공개 필드만 조회 가능
+    fn can_view_private_fields(&self, ctx: &Context<'_>) -> Result<bool> {
+        let auth_info = ctx.data::<AuthInfo>()?;
+        let scoped_owner = !auth_info.is_delegated()
+            && matches!(
+                auth_info.get_user_id_for(Scope::ProfileRead),
+                Ok(user_id) if user_id == self.uuid
+            );
+
+        Ok(scoped_owner || auth_info.check_owner_or_admin(&self.uuid).is_ok())
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct AuthMethod {
+    #[graphql(name = "type")]
+    pub ty: AuthMethodType,
+    pub identifier: String,
+}
+
+#[derive(SimpleObject)]
+pub struct Session {
+    pub id: ID,
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+    pub created_at: DateTimeScalar,
+    pub last_refreshed_at: DateTimeScalar,
+    pub current: bool,
+}
+
+#[derive(SimpleObject)]
+pub struct ApiKey {
+    pub id: ID,
+    pub name: String,
+    pub prefix: String,
+    pub scopes: Vec<Scope>,
+    pub created_at: DateTimeScalar,
+    pub expires_at: Option<DateTimeScalar>,
+    pub last_used_at: Option<DateTimeScalar>,
+}
+
+impl From<ApiKeyData> for ApiKey {
+    fn from(data: ApiKeyData) -> Self {
+        ApiKey {
+            id: ID(data.id.to_string()),
+            name: data.name,
+            prefix: data.prefix,
+            scopes: data.scopes,
+            created_at: DateTimeScalar(data.created_at),
+            expires_at: data.expires_at.map(DateTimeScalar),
+            last_used_at: data.last_used_at.map(DateTimeScalar),
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct CreatedApiKey {
+    pub api_key: ApiKey,
+    pub key: String,
+}
+
 #[derive(InputObject)]
 pub struct UserRegisterInput {
     pub nickname: String,
@@ -18,14 +197,59 @@ pub struct UserRegisterInput {
     pub email: String,
 }
 
+#[derive(InputObject)]
+pub struct UpdateProfileInput {
+    pub nickname: Option<String>,
+    #[graphql(validator(Email))]
+    pub email: Option<String>,
+    // 이메일을 변경할 때 필요
+    pub current_password: Option<String>,
+    pub avatar_url: MaybeUndefined<String>,
+    pub bio: MaybeUndefined<String>,
+}
+
+#[derive(InputObject)]
+pub struct LinkAuthMethodInput {
+    #[graphql(name = "type")]
+    pub ty: AuthMethodType,
+    #[graphql(validator(Email))]
+    pub email: Option<String>,
+    pub password: Option<String>,
+    pub code: Option<String>,
+}
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq)]
+pub enum OAuthProvider {
+    Kakao,
+    Google,
+    Facebook,
+}
+
 #[derive(SimpleObject)]
 pub struct LoginResult {
     pub access_token: String,
     pub refresh_token: String,
 }
 
+#[derive(SimpleObject)]
+pub struct TwoFactorChallenge {
+    pub challenge_token: String,
+}
+
+#[derive(Union)]
+pub enum LoginPayload {
+    LoginResult(LoginResult),
+    TwoFactorChallenge(TwoFactorChallenge),
+}
+
+#[derive(SimpleObject)]
+pub struct TwoFactorEnrollment {
+    pub secret: String,
+    pub uri: String,
+}
+
 #[derive(SimpleObject)]
 pub struct RefreshResult {
     pub access_token: String,
-    pub refresh_token: Option<String>,
+    pub refresh_token: String,
 }
@@ -0,0 +1,297 @@
+use std::{
+    future::Future,
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+use actix::{Actor, ActorContext, AsyncContext, StreamHandler};
+use actix_web::{error::ErrorBadRequest, web, HttpRequest, HttpResponse};
+use actix_web_actors::ws::{
+    self, CloseCode, CloseReason, Item, Message, ProtocolError, WebsocketContext,
+};
+use async_graphql::{
+    http::{WebSocket, WebSocketProtocols, WsMessage, ALL_WEBSOCKET_PROTOCOLS},
+    Data,
+};
+use futures::{
+    channel::{mpsc, oneshot},
+    future::{self, BoxFuture},
+    stream, FutureExt, Stream, StreamExt,
+};
+
+use sqlx::PgPool;
+
+use crate::{auth::connection_auth::ConnectionAuth, schema::AppSchema};
+
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(5);
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(10);
+// graphql-ws 프로토콜에서 인증 실패에 사용하는 코드
+const CLOSE_CODE_UNAUTHORIZED: u16 = 4401;
+
+type Initializer =
+    Box<dyn FnOnce(serde_json::Value) -> BoxFuture<'static, async_graphql::Result<Data>> + Send>;
+
+struct ConnectionRevoked;
+
+// WSSubscription과 같은 역할을 하지만, 토큰이 폐기되면 close code와 함께 연결을 끊음
+pub struct SubscriptionConnection {
+    schema: AppSchema,
+    protocol: WebSocketProtocols,
+    connection_auth: ConnectionAuth,
+    postgres_pool: PgPool,
+    redis_pool: deadpool_redis::Pool,
+    initializer: Option<Initializer>,
+    messages: Option<mpsc::UnboundedSender<Vec<u8>>>,
+    last_heartbeat: Instant,
+    continuation: Vec<u8>,
+}
+
+pub fn start_subscription<F, R>(
+    schema: AppSchema,
+    req: &HttpRequest,
+    stream: web::Payload,
+    connection_auth: ConnectionAuth,
+    postgres_pool: PgPool,
+    redis_pool: deadpool_redis::Pool,
+    initializer: F,
+) -> actix_web::Result<HttpResponse>
+where
+    F: FnOnce(serde_json::Value) -> R + Send + 'static,
+    R: Future<Output = async_graphql::Result<Data>> + Send + 'static,
+{
+    let protocol = req
+        .headers()
+        .get("sec-websocket-protocol")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|protocols| {
+            protocols
+                .split(',')
+                .find_map(|p| WebSocketProtocols::from_str(p.trim()).ok())
+        })
+        .ok_or_else(|| ErrorBadRequest("Unknown WebSocket protocol"))?;
+
+    ws::start_with_protocols(
+        SubscriptionConnection {
+            schema,
+            protocol,
+            connection_auth,
+            postgres_pool,
+            redis_pool,
+            initializer: Some(Box::new(move |value| initializer(value).boxed())),
+            messages: None,
+            last_heartbeat: Instant::now(),
+            continuation: vec![],
+        },
+        &ALL_WEBSOCKET_PROTOCOLS,
+        req,
+        stream,
+    )
+}
+
+// connection_init 결과가 인증된 연결이면 토큰이 폐기될 때 ConnectionRevoked를 내보냄
+fn watch_revocation(
+    initialized_rx: oneshot::Receiver<bool>,
+    connection_auth: ConnectionAuth,
+    postgres_pool: PgPool,
+    redis_pool: deadpool_redis::Pool,
+) -> impl Stream<Item = ConnectionRevoked> {
+    stream::once(async move {
+        match initialized_rx.await {
+            Ok(true) => {
+                connection_auth
+                    .wait_until_revoked(postgres_pool, redis_pool)
+                    .await;
+                Some(ConnectionRevoked)
+            }
+            _ => None,
+        }
+    })
+    .filter_map(future::ready)
+}
+
+fn revoked_close_reason() -> CloseReason {
+    CloseReason {
+        code: CloseCode::Other(CLOSE_CODE_UNAUTHORIZED),
+        description: Some("Invalidated auth token".to_owned()),
+    }
+}
+
+impl SubscriptionConnection {
+    fn send(&self, message: Vec<u8>) {
+        if let Some(messages) = &self.messages {
+            let _ = messages.unbounded_send(message);
+        }
+    }
+}
+
+impl Actor for SubscriptionConnection {
+    type Context = WebsocketContext<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        ctx.run_interval(HEARTBEAT_INTERVAL, |act, ctx| {
+            if Instant::now().duration_since(act.last_heartbeat) > CLIENT_TIMEOUT {
+                ctx.stop();
+            } else {
+                ctx.ping(b"");
+            }
+        });
+
+        // connection_init이 끝나 인증된 연결인 경우에만 토큰 폐기를 감시
+        let (initialized_tx, initialized_rx) = oneshot::channel();
+        let initializer = self.initializer.take();
+        let connection_auth = self.connection_auth.clone();
+        let data_initializer = move |value| async move {
+            let data = match initializer {
+                Some(initializer) => initializer(value).await?,
+                None => Data::default(),
+            };
+            let _ = initialized_tx.send(connection_auth.is_authenticated().await);
+            Ok(data)
+        };
+
+        let (tx, rx) = mpsc::unbounded();
+        ctx.add_stream(WebSocket::with_data(
+            self.schema.clone(),
+            rx,
+            data_initializer,
+            self.protocol,
+        ));
+        self.messages = Some(tx);
+
+        ctx.add_stream(watch_revocation(
+            initialized_rx,
+            self.connection_auth.clone(),
+            self.postgres_pool.clone(),
+            self.redis_pool.clone(),
+        ));
+    }
+}
+
+impl StreamHandler<Result<Message, ProtocolError>> for SubscriptionConnection {
+    fn handle(&mut self, message: Result<Message, ProtocolError>, ctx: &mut Self::Context) {
+        let message = match message {
+            Ok(message) => message,
+            Err(_) => return ctx.stop(),
+        };
+
+        match message {
+            Message::Ping(message) => {
+                self.last_heartbeat = Instant::now();
+                ctx.pong(&message);
+            }
+            Message::Pong(_) => self.last_heartbeat = Instant::now(),
+            Message::Text(text) => self.send(text.as_bytes().to_vec()),
+            Message::Binary(bytes) => self.send(bytes.to_vec()),
+            Message::Continuation(item) => match item {
+                Item::FirstText(bytes) | Item::FirstBinary(bytes) => {
+                    self.continuation = bytes.to_vec();
+                }
+                Item::Continue(bytes) => self.continuation.extend_from_slice(&bytes),
+                Item::Last(bytes) => {
+                    self.continuation.extend_from_slice(&bytes);
+                    let message = std::mem::take(&mut self.continuation);
+                    self.send(message);
+                }
+            },
+            Message::Close(_) => ctx.stop(),
+            Message::Nop => {}
+        }
+    }
+}
+
+impl StreamHandler<WsMessage> for SubscriptionConnection {
+    fn handle(&mut self, message: WsMessage, ctx: &mut Self::Context) {
+        match message {
+            WsMessage::Text(text) => ctx.text(text),
+            WsMessage::Close(code, description) => {
+                ctx.close(Some(CloseReason {
+                    code: code.into(),
+                    description: Some(description),
+                }));
+                ctx.stop();
+            }
+        }
+    }
+}
+
+impl StreamHandler<ConnectionRevoked> for SubscriptionConnection {
+    fn handle(&mut self, _: ConnectionRevoked, ctx: &mut Self::Context) {
+        ctx.close(Some(revoked_close_reason()));
+        ctx.stop();
+    }
+
+    // 인증되지 않은 연결은 감시하지 않고 끝나므로 연결은 유지
+    fn finished(&mut self, _: &mut Self::Context) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::time::timeout;
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::{
+        auth::{auth_info::AuthInfo, jwt::JWT_KEYS, login::Claims},
+        config::CONFIG,
+    };
+
+    async fn verified_auth_info(redis_pool: &deadpool_redis::Pool) -> AuthInfo {
+        let token = JWT_KEYS
+            .encode(&Claims::new(&Uuid::new_v4(), None).unwrap())
+            .unwrap();
+        let mut auth_info = AuthInfo::from_header(Some(format!("Bearer {}", token)));
+        assert!(auth_info.verify(&mut redis_pool.get().await.unwrap()).await);
+        auth_info
+    }
+
+    // Redis가 필요하므로 `cargo test -- --ignored`로 실행
+    #[tokio::test]
+    #[ignore]
+    async fn closes_connection_when_token_is_revoked() {
+        let postgres_pool = PgPool::connect_lazy(&CONFIG.database_url).unwrap();
+        let redis_pool = CONFIG.redis.create_pool().unwrap();
+        let auth_info = verified_auth_info(&redis_pool).await;
+        let connection_auth = ConnectionAuth::new(auth_info.clone());
+
+        let (initialized_tx, initialized_rx) = oneshot::channel();
+        let mut revoked = Box::pin(watch_revocation(
+            initialized_rx,
+            connection_auth,
+            postgres_pool,
+            redis_pool.clone(),
+        ));
+        initialized_tx.send(true).unwrap();
+
+        auth_info
+            .invalidate(&mut redis_pool.get().await.unwrap())
+            .await
+            .unwrap();
+
+        let received = timeout(Duration::from_secs(5), revoked.next())
+            .await
+            .expect("Revocation not detected");
+        assert!(received.is_some());
+        assert_eq!(
+            revoked_close_reason().code,
+            CloseCode::Other(CLOSE_CODE_UNAUTHORIZED)
+        );
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn does_not_watch_unauthenticated_connection() {
+        let postgres_pool = PgPool::connect_lazy(&CONFIG.database_url).unwrap();
+        let redis_pool = CONFIG.redis.create_pool().unwrap();
+
+        let (initialized_tx, initialized_rx) = oneshot::channel();
+        let mut revoked = Box::pin(watch_revocation(
+            initialized_rx,
+            ConnectionAuth::new(AuthInfo::from_header(None)),
+            postgres_pool,
+            redis_pool,
+        ));
+        initialized_tx.send(false).unwrap();
+
+        assert!(revoked.next().await.is_none());
+    }
+}